@@ -44,6 +44,7 @@ fn run_cli_benchmark<M: Measurement>(
 
         let sut_output = Command::new(binary)
             .current_dir(&test_working_dir)
+            .env("RT_CONFIG", "")
             .args(args)
             .args(&test_description.args)
             .output()
@@ -65,6 +66,7 @@ fn run_cli_benchmark<M: Measurement>(
         b.iter(|| {
             Command::new(binary)
                 .current_dir(&test_working_dir)
+                .env("RT_CONFIG", "")
                 .args(args)
                 .args(&test_description.args)
                 .stdout(Stdio::null())
@@ -1,6 +1,7 @@
 pub mod snapshot;
 
 use std::{
+    os::unix::fs::PermissionsExt as _,
     path::{Path, PathBuf},
     process::Command,
 };
@@ -23,7 +24,7 @@ impl TestWorkingDir {
             .into_os_string();
 
         if directory_snapshot_path.exists() {
-            std::fs::remove_dir_all(&directory_snapshot_path).unwrap();
+            remove_dir_all(&directory_snapshot_path);
         }
         std::fs::create_dir_all(&directory_snapshot_path).unwrap();
         Command::new(&absolute_setup_script_path)
@@ -50,6 +51,27 @@ impl AsRef<Path> for TestWorkingDir {
 
 impl Drop for TestWorkingDir {
     fn drop(&mut self) {
-        std::fs::remove_dir_all(&self.0).unwrap();
+        remove_dir_all(&self.0);
+    }
+}
+
+/// Removes a directory created by a setup script. Setup scripts may create
+/// unreadable directories, so permissions are restored first.
+fn remove_dir_all(path: &Path) {
+    restore_permissions(path);
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+fn restore_permissions(path: &Path) {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return;
+    };
+    if !metadata.is_dir() {
+        return;
+    }
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    for entry in std::fs::read_dir(path).unwrap() {
+        restore_permissions(&entry.unwrap().path());
     }
 }
@@ -1,9 +1,9 @@
-use std::ffi::OsString;
+use std::{ffi::OsString, path::PathBuf};
 
 use clap::Parser;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, disable_help_flag = true)]
 pub struct TreeArgs {
     // ============================ Listing options ============================
     #[arg(short = 'a')]
@@ -12,7 +12,9 @@ pub struct TreeArgs {
     #[arg(short = 'd')]
     /// List directories only.
     pub list_directories_only: bool,
-    // TODO: -l
+    #[arg(short = 'l')]
+    /// Follow symbolic links like directories.
+    pub follow_links: bool,
     #[arg(short = 'f')]
     /// Print the full path prefix for each file.
     pub print_full_path_prefix: bool,
@@ -20,12 +22,24 @@ pub struct TreeArgs {
     #[arg(short = 'L')]
     /// Descend only level directories deep.
     pub max_level: Option<usize>,
-    // TODO: -R
+    #[arg(short = 'R')]
+    /// Rerun tree when max dir level reached.
+    ///
+    /// Only has an effect with -H and -L.
+    pub rerun_at_max_level: bool,
     #[arg(short = 'P')]
     /// List only those files that match the pattern given.
+    ///
+    /// Unless --compat is set, patterns containing a slash are matched against
+    /// the path relative to the root, as in a .gitignore file, and a pattern
+    /// starting with ! excludes files matched by earlier patterns.
     pub file_include_patterns: Vec<String>,
     #[arg(short = 'I')]
     /// Do not list files that match the given pattern.
+    ///
+    /// Unless --compat is set, patterns containing a slash are matched against
+    /// the path relative to the root, as in a .gitignore file, and a pattern
+    /// starting with ! lists files excluded by earlier patterns.
     pub file_exclude_patterns: Vec<String>,
     #[arg(long = "ignore-case")]
     /// Ignore case when pattern matching.
@@ -36,33 +50,112 @@ pub struct TreeArgs {
     /// Requires the --compat option.
     pub match_dirs: bool,
     // TODO: --metafirst
-    // TODO: --prune
+    #[arg(long)]
+    /// Prune empty directories from the output.
+    pub prune: bool,
     // TODO: --info
     // TODO: --infofile
     #[arg(long = "noreport")]
     /// Turn off file/directory count at end of tree listing.
     pub no_report: bool,
-    // TODO: --charset
+    #[arg(long = "charset")]
+    /// Use charset X for terminal/HTML and indentation line output.
+    pub charset: Option<String>,
     // TODO: --filelimit
     #[arg(short = 'o')]
     /// Output to file instead of stdout.
     pub output_to_file: Option<OsString>,
 
     // ============================= File options ==============================
-    // TODO
+    #[arg(short = 'q')]
+    /// Print non-printable characters as '?'.
+    pub replace_non_printable: bool,
+    #[arg(short = 'N')]
+    /// Print non-printable characters as is.
+    pub raw_non_printable: bool,
+    #[arg(short = 'Q')]
+    /// Quote filenames with double quotes.
+    pub quote_names: bool,
+    // TODO: -p
+    // TODO: -u
+    // TODO: -g
+    #[arg(short = 's')]
+    /// Print the size in bytes of each file.
+    pub print_size: bool,
+    #[arg(short = 'h')]
+    /// Print the size in a more human readable way.
+    pub human_readable: bool,
+    #[arg(long = "si")]
+    /// Like -h, but use in SI units (powers of 1000).
+    pub si_units: bool,
+    #[arg(long = "du")]
+    /// Compute size of directories by their contents.
+    pub disk_usage: bool,
+    // TODO: -D
+    // TODO: --timefmt
+    #[arg(short = 'F')]
+    /// Appends '/', '=', '*', '@', '|' or '>' as per ls -F.
+    pub classify: bool,
+    // TODO: --inodes
+    // TODO: --device
 
     // ============================ Sorting options ============================
-    // TODO
+    // TODO: -v
+    // TODO: -t
+    // TODO: -c
+    // TODO: -U
+    #[arg(short = 'r')]
+    /// Reverse the order of the sort.
+    pub reverse: bool,
+    // TODO: --dirsfirst
+    // TODO: --filesfirst
+    #[arg(long = "sort", value_name = "X", value_parser = ["name", "size"])]
+    /// Select sort: name,size.
+    pub sort: Option<String>,
 
     // =========================== Graphics options ============================
-    // TODO
+    // TODO: -i
+    // TODO: -A
+    // TODO: -S
+    #[arg(short = 'n')]
+    /// Turn colorization off always (-C overrides).
+    pub no_color: bool,
+    #[arg(short = 'C')]
+    /// Turn colorization on always.
+    pub color: bool,
+    #[arg(
+        long = "hyperlink",
+        value_name = "WHEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always",
+        value_parser = ["auto", "always", "never"]
+    )]
+    /// Turn on OSC 8 terminal hyperlinks (auto, always or never).
+    ///
+    /// With auto, hyperlinks are only written to a terminal, never to the -o
+    /// file.
+    pub hyperlink: Option<String>,
 
     // ========================= XML/HTML/JSON options =========================
-    // TODO
+    // TODO: -X
+    #[arg(short = 'J')]
+    /// Prints out a JSON representation of the tree.
+    pub json: bool,
+    #[arg(short = 'H', value_name = "baseHREF")]
+    /// Prints out HTML format with baseHREF as top directory.
+    pub html_base_href: Option<String>,
+    // TODO: -T
+    // TODO: --nolinks
 
     // ============================= Input options =============================
     // TODO
 
+    // ========================= Miscellaneous options =========================
+    #[arg(long, action = clap::ArgAction::Help)]
+    /// Print usage and this help message and exit.
+    pub help: Option<bool>,
+
     // ============================ Riptree options ============================
     #[arg(long)]
     /// Enable compatibility mode. Makes riptree2 behave the same as tree.
@@ -77,6 +170,11 @@ pub struct TreeArgs {
     ///
     /// Requires the --compat option.
     pub gitignore: bool,
+    #[arg(long, value_name = "FILE", conflicts_with = "no_gitignore")]
+    /// Explicitly read a gitignore file.
+    ///
+    /// Implies --gitignore in compat mode.
+    pub gitfile: Vec<PathBuf>,
     #[arg(long, requires = "compat")]
     /// Show Nerd Fonts icons.
     ///
@@ -87,6 +185,128 @@ pub struct TreeArgs {
     ///
     /// Incompatible with the --compat option.
     pub no_icons: bool,
+    #[arg(long, value_name = "FILE", conflicts_with = "compat")]
+    /// List only those files that match a pattern read from the file, in
+    /// addition to those matching -P.
+    ///
+    /// The file contains one pattern per line. Empty lines and lines starting
+    /// with # are ignored. Incompatible with the --compat option.
+    pub include_from: Vec<PathBuf>,
+    #[arg(long, value_name = "FILE", conflicts_with = "compat")]
+    /// Do not list files that match a pattern read from the file.
+    ///
+    /// The file contains one pattern per line. Empty lines and lines starting
+    /// with # are ignored. Incompatible with the --compat option.
+    pub exclude_from: Vec<PathBuf>,
+    #[arg(long, value_name = "REGEX", conflicts_with = "compat")]
+    /// List only those files whose name matches the regex, in addition to
+    /// those matching -P.
+    ///
+    /// Regexes containing a slash are matched against the path relative to the
+    /// root. Incompatible with the --compat option.
+    pub regex_include: Vec<String>,
+    #[arg(long, value_name = "REGEX", conflicts_with = "compat")]
+    /// Do not list files whose name matches the regex.
+    ///
+    /// Regexes containing a slash are matched against the path relative to the
+    /// root. Incompatible with the --compat option.
+    pub regex_exclude: Vec<String>,
+    #[arg(
+        long = "type",
+        value_name = "TYPE",
+        value_parser = ["f", "file", "d", "directory", "l", "symlink", "x", "executable", "s", "socket", "p", "pipe", "e", "empty"],
+        conflicts_with = "compat"
+    )]
+    /// List only files of the given type: f (file), d (directory), l
+    /// (symlink), x (executable), s (socket), p (pipe) or e (empty).
+    ///
+    /// Directories are listed regardless, unless --prune is given. Incompatible
+    /// with the --compat option.
+    pub file_type: Vec<String>,
+    #[arg(
+        long,
+        value_name = "SIZE",
+        allow_hyphen_values = true,
+        conflicts_with = "compat"
+    )]
+    /// List only regular files of at least (+SIZE), at most (-SIZE) or exactly
+    /// SIZE bytes, e.g. +10M or -1ki.
+    ///
+    /// Incompatible with the --compat option.
+    pub size: Vec<String>,
+    #[arg(
+        long,
+        value_name = "DURATION|DATE",
+        visible_alias = "changed-within",
+        conflicts_with = "compat"
+    )]
+    /// List only files modified within the given duration (e.g. 2h or 1w), or
+    /// after the given date (e.g. 2024-01-31 or "2024-01-31 12:00:00").
+    ///
+    /// Incompatible with the --compat option.
+    pub newer: Option<String>,
+    #[arg(long, value_name = "DURATION|DATE", conflicts_with = "compat")]
+    /// List only files modified before the given duration ago, or before the
+    /// given date.
+    ///
+    /// Incompatible with the --compat option.
+    pub older: Option<String>,
+    #[arg(long, value_name = "N", conflicts_with = "compat")]
+    /// List at most N entries of each directory.
+    ///
+    /// Incompatible with the --compat option.
+    pub max_entries: Option<usize>,
+    #[arg(long, requires = "max_entries")]
+    /// Include entries hidden by --max-entries in the report.
+    pub count_elided: bool,
+    #[arg(long, conflicts_with = "compat")]
+    /// Annotate directories which are not descended into because of -L with
+    /// the number of directories and files they contain, e.g. [2 dirs, 5
+    /// files].
+    ///
+    /// Incompatible with the --compat option.
+    pub show_truncated: bool,
+    #[arg(long, requires = "show_truncated")]
+    /// Include the entries counted by --show-truncated in the report.
+    pub count_truncated: bool,
+    #[arg(long, conflicts_with = "compat")]
+    /// Print directories whose only entry is a directory on the same line as
+    /// it, e.g. com/example/project.
+    ///
+    /// Each collapsed directory still counts as a level for -L. Incompatible
+    /// with the --compat option.
+    pub collapse: bool,
+    #[arg(
+        long,
+        value_name = "MODE",
+        value_parser = ["absolute", "relative"],
+        conflicts_with = "compat"
+    )]
+    /// Print the absolute path, or the path relative to the current directory,
+    /// of each file.
+    ///
+    /// Incompatible with the --compat option.
+    pub full_path: Option<String>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["compat", "full_path"]
+    )]
+    /// Print the path of each file relative to DIR.
+    ///
+    /// Incompatible with the --compat option.
+    pub relative_to: Option<OsString>,
+    #[arg(long, requires = "output_to_file", conflicts_with = "compat")]
+    /// Write the -o file atomically, replacing it only once the tree has been
+    /// written completely.
+    ///
+    /// Incompatible with the --compat option.
+    pub atomic: bool,
+    #[arg(long)]
+    /// Do not load defaults from config files.
+    ///
+    /// Config files are never loaded in compat mode.
+    pub no_config: bool,
 
     // ================================= Roots =================================
     #[arg()]
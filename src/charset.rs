@@ -0,0 +1,87 @@
+/// Environment variable used by the reference implementation to override the
+/// charset.
+pub static TREE_CHARSET_ENV_VAR: &str = "TREE_CHARSET";
+
+/// The characters used to draw the lines of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Utf8,
+    Ascii,
+}
+
+impl Charset {
+    /// Returns the charset with the given name. Like the reference
+    /// implementation, names are case insensitive and unknown charsets fall
+    /// back to plain ASCII line drawing.
+    pub fn from_name(name: &str) -> Self {
+        if name.eq_ignore_ascii_case("UTF-8") || name.eq_ignore_ascii_case("utf8") {
+            Self::Utf8
+        } else {
+            Self::Ascii
+        }
+    }
+
+    /// Detects the charset in the same way as the reference implementation,
+    /// i.e. from the `TREE_CHARSET` environment variable, or else from the
+    /// codeset of the `LC_CTYPE` locale.
+    pub fn from_env() -> Self {
+        if let Some(charset) = std::env::var_os(TREE_CHARSET_ENV_VAR) {
+            return Self::from_name(&charset.to_string_lossy());
+        }
+
+        if locale_codeset_is_utf8() {
+            Self::Utf8
+        } else {
+            Self::Ascii
+        }
+    }
+
+    /// Indentation used below a directory which has further siblings.
+    #[inline]
+    pub fn vertical(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "│\u{a0}\u{a0} ",
+            Self::Ascii => "|   ",
+        }
+    }
+
+    /// Prefix used for entries which have further siblings.
+    #[inline]
+    pub fn branch(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "├── ",
+            Self::Ascii => "|-- ",
+        }
+    }
+
+    /// Prefix used for the last entry in a directory.
+    #[inline]
+    pub fn corner(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "└── ",
+            Self::Ascii => "`-- ",
+        }
+    }
+}
+
+/// Resolves the `LC_CTYPE` locale from the environment with the same
+/// precedence as `setlocale`, and checks whether its codeset is UTF-8.
+pub(crate) fn locale_codeset_is_utf8() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .find(|value| !value.is_empty());
+
+    let Some(locale) = locale else {
+        return false;
+    };
+    let locale = locale.to_string_lossy();
+
+    // Locales have the form language[_territory][.codeset][@modifier]
+    let Some((_, codeset)) = locale.split_once('.') else {
+        return false;
+    };
+    let codeset = codeset.split('@').next().unwrap_or_default();
+
+    codeset.eq_ignore_ascii_case("UTF-8") || codeset.eq_ignore_ascii_case("utf8")
+}
@@ -0,0 +1,226 @@
+use std::{
+    fs::Metadata,
+    io::IsTerminal as _,
+    os::unix::fs::{FileTypeExt as _, PermissionsExt as _},
+};
+
+/// Environment variable used by the reference implementation to configure
+/// colors. Takes precedence over `LS_COLORS`.
+pub static TREE_COLORS_ENV_VAR: &str = "TREE_COLORS";
+
+/// Environment variable used by `ls` (and the reference implementation) to
+/// configure colors.
+pub static LS_COLORS_ENV_VAR: &str = "LS_COLORS";
+
+/// The colors used by the reference implementation when colorization is
+/// forced, but neither `TREE_COLORS` nor `LS_COLORS` are set.
+static DEFAULT_COLORS: &str = ":no=00:rs=0:fi=00:di=01;34:ln=01;36:pi=40;33:so=01;35:bd=40;33;01:cd=40;33;01:or=40;31;01:ex=01;32:*.bat=01;32:*.BAT=01;32:*.btm=01;32:*.BTM=01;32:*.cmd=01;32:*.CMD=01;32:*.com=01;32:*.COM=01;32:*.dll=01;32:*.DLL=01;32:*.exe=01;32:*.EXE=01;32:*.arj=01;31:*.bz2=01;31:*.deb=01;31:*.gz=01;31:*.lzh=01;31:*.rpm=01;31:*.tar=01;31:*.taz=01;31:*.tb2=01;31:*.tbz2=01;31:*.tbz=01;31:*.tgz=01;31:*.tz2=01;31:*.z=01;31:*.Z=01;31:*.zip=01;31:*.ZIP=01;31:*.zoo=01;31:*.asf=01;35:*.ASF=01;35:*.avi=01;35:*.AVI=01;35:*.bmp=01;35:*.BMP=01;35:*.flac=01;35:*.FLAC=01;35:*.gif=01;35:*.GIF=01;35:*.jpg=01;35:*.JPG=01;35:*.jpeg=01;35:*.JPEG=01;35:*.m2a=01;35:*.M2a=01;35:*.m2v=01;35:*.M2V=01;35:*.mov=01;35:*.MOV=01;35:*.mp3=01;35:*.MP3=01;35:*.mpeg=01;35:*.MPEG=01;35:*.mpg=01;35:*.MPG=01;35:*.nuv=01;35:*.NUV=01;35:*.ogg=01;35:*.OGG=01;35:*.ogm=01;35:*.OGM=01;35:*.qt=01;35:*.QT=01;35:*.rm=01;35:*.RM=01;35:*.tif=01;35:*.TIF=01;35:*.tiff=01;35:*.TIFF=01;35:*.vob=01;35:*.VOB=01;35:*.wav=01;35:*.WAV=01;35:*.wmv=01;35:*.WMV=01;35:*.xcf=01;35:*.XCF=01;35:*.xpm=01;35:*.XPM=01;35:*.xwd=01;35:*.XWD=01;35:";
+
+/// Colors parsed from a `dircolors` style specification (the format used by
+/// `LS_COLORS` and `TREE_COLORS`).
+#[derive(Debug, Clone)]
+pub struct Colors {
+    left_code: String,
+    right_code: String,
+    end_code: String,
+    dir: Option<String>,
+    link: Option<String>,
+    /// Set by `ln=target`, in which case links are colored like their target.
+    link_as_target: bool,
+    fifo: Option<String>,
+    socket: Option<String>,
+    block_device: Option<String>,
+    char_device: Option<String>,
+    orphan: Option<String>,
+    missing: Option<String>,
+    exec: Option<String>,
+    setuid: Option<String>,
+    setgid: Option<String>,
+    extensions: Vec<(String, String)>,
+}
+
+impl Colors {
+    /// Parses a `dircolors` style specification, e.g. `di=01;34:*.txt=32`.
+    /// Unknown keys are ignored.
+    pub fn parse(spec: &str) -> Self {
+        let mut left_code = None;
+        let mut right_code = None;
+        let mut end_code = None;
+        let mut normal = None;
+        let mut colors = Self {
+            left_code: String::new(),
+            right_code: String::new(),
+            end_code: String::new(),
+            dir: None,
+            link: None,
+            link_as_target: false,
+            fifo: None,
+            socket: None,
+            block_device: None,
+            char_device: None,
+            orphan: None,
+            missing: None,
+            exec: None,
+            setuid: None,
+            setgid: None,
+            extensions: Vec::new(),
+        };
+
+        for (key, value) in spec.split(':').filter_map(|entry| entry.split_once('=')) {
+            let value = Some(value.to_string());
+            match key {
+                "no" => normal = value,
+                "di" => colors.dir = value,
+                "ln" if value.as_deref() == Some("target") => colors.link_as_target = true,
+                "ln" => colors.link = value,
+                "pi" => colors.fifo = value,
+                "so" => colors.socket = value,
+                "bd" => colors.block_device = value,
+                "cd" => colors.char_device = value,
+                "or" => colors.orphan = value,
+                "mi" => colors.missing = value,
+                "ex" => colors.exec = value,
+                "su" => colors.setuid = value,
+                "sg" => colors.setgid = value,
+                "lc" => left_code = value,
+                "rc" => right_code = value,
+                "ec" => end_code = value,
+                _ => {
+                    if let Some(extension) = key.strip_prefix('*')
+                        && let Some(value) = value
+                    {
+                        colors.extensions.push((extension.to_string(), value));
+                    }
+                }
+            }
+        }
+
+        colors.left_code = left_code.unwrap_or_else(|| "\x1b[".to_string());
+        colors.right_code = right_code.unwrap_or_else(|| "m".to_string());
+        colors.end_code = end_code.unwrap_or_else(|| {
+            format!(
+                "{}{}{}",
+                colors.left_code,
+                normal.as_deref().unwrap_or("00"),
+                colors.right_code
+            )
+        });
+
+        colors
+    }
+
+    /// Decides whether to colorize the output in the same way as the
+    /// reference implementation, and if so, returns the colors configured by
+    /// the `TREE_COLORS` or `LS_COLORS` environment variables.
+    ///
+    /// `force_color` and `no_color` correspond to the -C and -n options.
+    pub fn from_env(force_color: bool, no_color: bool) -> Option<Self> {
+        std::env::var_os("TERM")?;
+
+        let force_color =
+            force_color || (std::env::var_os("CLICOLOR_FORCE").is_some() && !no_color);
+        let no_color = no_color
+            || std::env::var_os("NO_COLOR")
+                .map(|value| !value.is_empty())
+                .unwrap_or(false);
+
+        // Like the reference implementation, an empty TREE_COLORS falls back to
+        // LS_COLORS
+        let non_empty_var = |name| std::env::var(name).ok().filter(|spec| !spec.is_empty());
+        let spec = non_empty_var(TREE_COLORS_ENV_VAR).or_else(|| non_empty_var(LS_COLORS_ENV_VAR));
+        let spec = match spec {
+            Some(spec) => Some(spec),
+            _ if force_color || std::env::var_os("CLICOLOR").is_some() => {
+                Some(DEFAULT_COLORS.to_string())
+            }
+            _ => None,
+        }?;
+
+        if !force_color && (no_color || !std::io::stdout().is_terminal()) {
+            return None;
+        }
+
+        Some(Self::parse(&spec))
+    }
+
+    /// Returns the escape sequence which ends a colored name.
+    #[inline]
+    pub fn end_code(&self) -> &str {
+        &self.end_code
+    }
+
+    /// Returns the escape sequence which starts a colored name, or `None` if
+    /// the name should not be colored.
+    ///
+    /// `metadata` should not follow symlinks. `orphan` indicates a symlink
+    /// whose target does not exist, and `link_target` indicates that the name
+    /// is the target of a symlink rather than an entry.
+    pub fn start_code(
+        &self,
+        metadata: Option<&Metadata>,
+        name: &[u8],
+        orphan: bool,
+        link_target: bool,
+    ) -> Option<String> {
+        self.code(metadata, name, orphan, link_target)
+            .map(|code| format!("{}{code}{}", self.left_code, self.right_code))
+    }
+
+    fn code(
+        &self,
+        metadata: Option<&Metadata>,
+        name: &[u8],
+        orphan: bool,
+        link_target: bool,
+    ) -> Option<&str> {
+        if orphan {
+            let code = if link_target {
+                self.missing.as_deref()
+            } else {
+                self.orphan.as_deref()
+            };
+            if code.is_some() {
+                return code;
+            }
+        }
+
+        if let Some(metadata) = metadata {
+            let file_type = metadata.file_type();
+            if file_type.is_fifo() {
+                return self.fifo.as_deref();
+            } else if file_type.is_char_device() {
+                return self.char_device.as_deref();
+            } else if file_type.is_dir() {
+                return self.dir.as_deref();
+            } else if file_type.is_block_device() {
+                return self.block_device.as_deref();
+            } else if file_type.is_symlink() {
+                return self.link.as_deref();
+            } else if file_type.is_socket() {
+                return self.socket.as_deref();
+            } else if file_type.is_file() {
+                let mode = metadata.permissions().mode();
+                if mode & 0o4000 != 0 && self.setuid.is_some() {
+                    return self.setuid.as_deref();
+                }
+                if mode & 0o2000 != 0 && self.setgid.is_some() {
+                    return self.setgid.as_deref();
+                }
+                if mode & 0o111 != 0 && self.exec.is_some() {
+                    return self.exec.as_deref();
+                }
+            }
+        }
+
+        self.extensions
+            .iter()
+            .find(|(extension, _)| name.ends_with(extension.as_bytes()))
+            .map(|(_, code)| code.as_str())
+    }
+
+    /// Whether symlinks should be colored according to their target.
+    #[inline]
+    pub fn link_as_target(&self) -> bool {
+        self.link_as_target
+    }
+}
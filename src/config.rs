@@ -0,0 +1,257 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, anyhow, bail};
+use clap::{Arg, ArgMatches, CommandFactory as _, FromArgMatches as _, parser::ValueSource};
+use toml::{Table, Value};
+
+use crate::args::TreeArgs;
+
+/// Environment variable which overrides the location of the user config file.
+/// If it is set to an empty string, the user config file is not loaded.
+pub static CONFIG_ENV_VAR: &str = "RT_CONFIG";
+
+/// Name of the per-project config file, which is discovered by searching
+/// upwards from the (first) root directory.
+pub static PROJECT_CONFIG_FILE_NAME: &str = ".riptree.toml";
+
+/// Options which may not be set from a config file.
+static DISALLOWED_KEYS: [&str; 4] = ["compat", "help", "no_config", "roots"];
+
+/// Parses the command line arguments of the current process, using the config
+/// files as defaults for any options which were not passed explicitly.
+pub fn parse_args() -> anyhow::Result<TreeArgs> {
+    parse_args_from(std::env::args_os())
+}
+
+/// Parses the given command line arguments, using the config files as defaults
+/// for any options which were not passed explicitly.
+///
+/// Config files are ignored entirely if `--compat` or `--no-config` is passed.
+/// Otherwise, the user config file (`$XDG_CONFIG_HOME/riptree2/config.toml`,
+/// or the file specified by `RT_CONFIG`) is loaded first, and then the nearest
+/// `.riptree.toml` takes precedence over it. Keys are the names of the fields
+/// of [`TreeArgs`], e.g. `max_level = 2` or `no_icons = true`. A key set to
+/// `false` turns off the same key in a file with lower precedence.
+///
+/// Options from config files which were passed on the command line, or which
+/// conflict with an option passed on the command line, are ignored. Options
+/// which override each other don't need to be skipped, since the command line
+/// arguments are parsed after the config file arguments and so win.
+pub fn parse_args_from<I, T>(args: I) -> anyhow::Result<TreeArgs>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let mut args = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+    let bin_name = if args.is_empty() {
+        OsString::from("rt")
+    } else {
+        args.remove(0)
+    };
+
+    let command_line_matches =
+        TreeArgs::command().get_matches_from(std::iter::once(&bin_name).chain(&args));
+    let command_line_args = TreeArgs::from_arg_matches(&command_line_matches)?;
+
+    if command_line_args.compat || command_line_args.no_config {
+        return Ok(command_line_args);
+    }
+
+    let search_root = command_line_args
+        .roots
+        .first()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let config_files = [user_config_path()?, project_config_path(&search_root)]
+        .into_iter()
+        .flatten()
+        .map(|path| ConfigFile::load(&path))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if config_files.is_empty() {
+        return Ok(command_line_args);
+    }
+
+    let mut config_args = Vec::new();
+    for (key, value, path) in merge_config_files(&config_files) {
+        let key_args = config_value_to_args(key, value)
+            .with_context(|| format!("Invalid config file ({})", path.to_string_lossy()))?;
+        if command_line_matches.value_source(key) == Some(ValueSource::CommandLine)
+            || conflicts_with_command_line(&command_line_matches, key)
+        {
+            continue;
+        }
+        TreeArgs::command()
+            .try_get_matches_from(std::iter::once(&bin_name).chain(&key_args))
+            .map_err(|err| {
+                let message = err.render().to_string();
+                let message = message.lines().next().unwrap_or_default();
+                anyhow!("{}", message.trim_start_matches("error: ").to_string())
+            })
+            .with_context(|| format!("Invalid value for option {key:?}"))
+            .with_context(|| format!("Invalid config file ({})", path.to_string_lossy()))?;
+        config_args.extend(key_args);
+    }
+
+    let matches = TreeArgs::command()
+        .get_matches_from(std::iter::once(&bin_name).chain(&config_args).chain(&args));
+
+    Ok(TreeArgs::from_arg_matches(&matches)?)
+}
+
+/// Returns whether the option conflicts with an option which was passed on the
+/// command line. Conflicts are only declared on one of the two options, so
+/// both directions are checked.
+fn conflicts_with_command_line(command_line_matches: &ArgMatches, key: &str) -> bool {
+    let command = TreeArgs::command();
+    let Some(arg) = command.get_arguments().find(|arg| arg.get_id() == key) else {
+        return false;
+    };
+    let conflicts = |arg: &Arg, other: &Arg| {
+        command
+            .get_arg_conflicts_with(arg)
+            .iter()
+            .any(|conflict| conflict.get_id() == other.get_id())
+    };
+
+    command
+        .get_arguments()
+        .filter(|other| {
+            command_line_matches.value_source(other.get_id().as_str())
+                == Some(ValueSource::CommandLine)
+        })
+        .any(|other| conflicts(arg, other) || conflicts(other, arg))
+}
+
+struct ConfigFile {
+    path: PathBuf,
+    table: Table,
+}
+
+impl ConfigFile {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file ({})", path.to_string_lossy()))?;
+        let table = contents
+            .parse::<Table>()
+            .with_context(|| format!("Failed to parse config file ({})", path.to_string_lossy()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            table,
+        })
+    }
+}
+
+/// Merges config files, with later files taking precedence over earlier ones.
+/// Returns each key along with its value and the file it was taken from.
+fn merge_config_files(config_files: &[ConfigFile]) -> Vec<(&str, &Value, &Path)> {
+    let mut merged: Vec<(&str, &Value, &Path)> = Vec::new();
+
+    for config_file in config_files {
+        for (key, value) in &config_file.table {
+            let entry = (key.as_str(), value, config_file.path.as_path());
+            match merged
+                .iter_mut()
+                .find(|(merged_key, _, _)| *merged_key == key)
+            {
+                Some(merged_entry) => *merged_entry = entry,
+                None => merged.push(entry),
+            }
+        }
+    }
+
+    merged
+}
+
+/// Converts a config value into the equivalent command line arguments.
+fn config_value_to_args(key: &str, value: &Value) -> anyhow::Result<Vec<OsString>> {
+    if DISALLOWED_KEYS.contains(&key) {
+        bail!("Option {key:?} cannot be set in a config file");
+    }
+
+    let command = TreeArgs::command();
+    let arg = command
+        .get_arguments()
+        .find(|arg| arg.get_id() == key && !arg.is_positional())
+        .ok_or_else(|| anyhow!("Unknown option {key:?}"))?;
+
+    let mut args = Vec::new();
+    match value {
+        Value::Boolean(true) => args.push(flag(arg)),
+        Value::Boolean(false) => {}
+        Value::Array(values) => {
+            for value in values {
+                args.push(flag_with_value(arg, scalar_to_string(key, value)?));
+            }
+        }
+        value => args.push(flag_with_value(arg, scalar_to_string(key, value)?)),
+    }
+
+    Ok(args)
+}
+
+fn scalar_to_string(key: &str, value: &Value) -> anyhow::Result<String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        _ => bail!("Unsupported value for option {key:?} ({value})"),
+    }
+}
+
+fn flag(arg: &Arg) -> OsString {
+    match (arg.get_long(), arg.get_short()) {
+        (Some(long), _) => format!("--{long}").into(),
+        (None, Some(short)) => format!("-{short}").into(),
+        (None, None) => unreachable!("non-positional arguments always have a flag"),
+    }
+}
+
+fn flag_with_value(arg: &Arg, value: String) -> OsString {
+    let mut flag = flag(arg);
+    flag.push("=");
+    flag.push(value);
+    flag
+}
+
+/// Returns the path of the user config file, if one should be loaded.
+fn user_config_path() -> anyhow::Result<Option<PathBuf>> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV_VAR) {
+        if path.is_empty() {
+            return Ok(None);
+        }
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            bail!(
+                "Config file specified by {CONFIG_ENV_VAR} does not exist ({})",
+                path.to_string_lossy()
+            );
+        }
+        return Ok(Some(path));
+    }
+
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => PathBuf::from(config_home),
+        _ => match std::env::var_os("HOME") {
+            Some(home) if !home.is_empty() => PathBuf::from(home).join(".config"),
+            _ => return Ok(None),
+        },
+    };
+    let path = config_home.join("riptree2").join("config.toml");
+
+    Ok(path.is_file().then_some(path))
+}
+
+/// Returns the path of the nearest project config file, searching upwards from
+/// the given directory.
+fn project_config_path(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(PROJECT_CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
@@ -1,30 +1,92 @@
 use std::{
+    cell::OnceCell,
     ffi::OsString,
-    fs::{DirEntry, FileType},
-    os::unix::ffi::OsStrExt as _,
+    fs::{DirEntry, FileType, Metadata},
+    os::unix::{ffi::OsStrExt as _, fs::FileTypeExt as _},
     path::{Path, PathBuf},
 };
 
-use anyhow::Context as _;
+use crate::error::{Error, Result};
 
+/// The kind of a file, as reported by `lstat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+    Unknown,
+}
+
+impl From<FileType> for FileKind {
+    fn from(ty: FileType) -> Self {
+        if ty.is_file() {
+            Self::File
+        } else if ty.is_dir() {
+            Self::Directory
+        } else if ty.is_symlink() {
+            Self::Symlink
+        } else if ty.is_fifo() {
+            Self::Fifo
+        } else if ty.is_socket() {
+            Self::Socket
+        } else if ty.is_block_device() {
+            Self::BlockDevice
+        } else if ty.is_char_device() {
+            Self::CharDevice
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Entry {
     file_name: OsString,
     path: PathBuf,
     ty: FileType,
+    metadata: OnceCell<Option<Metadata>>,
+    target_metadata: OnceCell<Option<Metadata>>,
+    disk_usage: OnceCell<u64>,
 }
 
 impl Entry {
-    pub(crate) fn new(entry: DirEntry) -> anyhow::Result<Self> {
+    pub(crate) fn new(entry: DirEntry) -> Result<Self> {
         let file_name = entry.file_name();
         let path = entry.path();
-        let ty = entry
-            .file_type()
-            .with_context(|| format!("Failed to get file type of {}", path.to_string_lossy()))?;
+        let ty = match entry.file_type() {
+            Ok(ty) => ty,
+            Err(source) => return Err(Error::ReadDir { path, source }),
+        };
 
         Ok(Self {
             file_name,
             path,
             ty,
+            metadata: OnceCell::new(),
+            target_metadata: OnceCell::new(),
+            disk_usage: OnceCell::new(),
+        })
+    }
+
+    /// Creates an entry for the root of a tree. The file name of the entry is
+    /// the path as given, and symlinks are followed.
+    pub(crate) fn root(path: PathBuf) -> Result<Self> {
+        let metadata = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(source) => return Err(Error::ReadDir { path, source }),
+        };
+
+        Ok(Self {
+            file_name: path.clone().into_os_string(),
+            path,
+            ty: metadata.file_type(),
+            metadata: OnceCell::new(),
+            target_metadata: OnceCell::new(),
+            disk_usage: OnceCell::new(),
         })
     }
 
@@ -36,10 +98,63 @@ impl Entry {
         self.ty
     }
 
+    pub fn kind(&self) -> FileKind {
+        self.ty.into()
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
 
+    /// Returns the metadata of the entry, without following symlinks. The
+    /// metadata is fetched the first time it is requested, and `None` is
+    /// returned if it could not be read.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata
+            .get_or_init(|| std::fs::symlink_metadata(&self.path).ok())
+            .as_ref()
+    }
+
+    /// Returns the metadata of the entry, following symlinks. Returns `None`
+    /// if the metadata could not be read, e.g. because the entry is a symlink
+    /// whose target does not exist.
+    pub fn target_metadata(&self) -> Option<&Metadata> {
+        if !self.ty.is_symlink() {
+            return self.metadata();
+        }
+
+        self.target_metadata
+            .get_or_init(|| std::fs::metadata(&self.path).ok())
+            .as_ref()
+    }
+
+    /// The size of the entry in bytes, without following symlinks. When the
+    /// tree is built with --du, the size of a directory includes the sizes of
+    /// its contents.
+    pub fn size(&self) -> u64 {
+        match self.disk_usage.get() {
+            Some(disk_usage) => *disk_usage,
+            None => self.metadata().map(Metadata::len).unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn set_disk_usage(&self, disk_usage: u64) {
+        let _ = self.disk_usage.set(disk_usage);
+    }
+
+    /// Whether the entry is a directory, or a symlink to a directory. The
+    /// reference implementation treats both as directories, e.g. when counting
+    /// them.
+    pub fn target_is_dir(&self) -> bool {
+        self.ty.is_dir()
+            || (self.ty.is_symlink() && self.target_metadata().is_some_and(Metadata::is_dir))
+    }
+
+    /// Whether the entry is a symlink whose target does not exist.
+    pub fn is_orphan(&self) -> bool {
+        self.ty.is_symlink() && self.target_metadata().is_none()
+    }
+
     pub fn is_hidden(&self) -> bool {
         // SAFETY: File names are always at least one byte long
         unsafe { self.file_name.as_bytes().first().unwrap_unchecked() == &b'.' }
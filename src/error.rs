@@ -0,0 +1,127 @@
+use std::{fmt, io, path::PathBuf};
+
+/// Errors returned by the riptree2 library.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A pattern passed to -P or -I could not be compiled.
+    InvalidPattern {
+        pattern: String,
+        source: globset::Error,
+    },
+    /// A pattern in a file passed to --include-from or --exclude-from could not
+    /// be compiled.
+    InvalidPatternFile {
+        path: PathBuf,
+        /// The line number of the pattern, starting from 1.
+        line: usize,
+        pattern: String,
+        source: globset::Error,
+    },
+    /// A file passed to --include-from or --exclude-from could not be read.
+    ReadPatternFile { path: PathBuf, source: io::Error },
+    /// A regex passed to --regex-include or --regex-exclude could not be
+    /// compiled.
+    InvalidRegex {
+        pattern: String,
+        source: regex::Error,
+    },
+    /// The options are invalid, or cannot be used together.
+    InvalidOptions(String),
+    /// A directory or one of its entries could not be read.
+    ReadDir { path: PathBuf, source: io::Error },
+    /// The target of a symlink could not be read.
+    ReadLink { path: PathBuf, source: io::Error },
+    /// A symlink was not followed (-l) because it links to a directory which
+    /// has already been listed.
+    RecursiveLink { path: PathBuf },
+    /// A path could not be canonicalized.
+    Canonicalize { path: PathBuf, source: io::Error },
+    /// An ignore file could not be loaded.
+    LoadIgnoreFile {
+        path: PathBuf,
+        source: ignore::Error,
+    },
+    /// The global gitignore file (git's core.excludesFile) could not be
+    /// loaded.
+    LoadGlobalIgnoreFile(ignore::Error),
+    /// The output could not be written.
+    Write(io::Error),
+    /// A file written in addition to the output, e.g. an HTML page for a
+    /// directory cut off by -L (-R), could not be written.
+    WriteFile { path: PathBuf, source: io::Error },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPattern { pattern, .. } => write!(f, "Invalid pattern ({pattern})"),
+            Self::InvalidPatternFile {
+                path,
+                line,
+                pattern,
+                ..
+            } => write!(
+                f,
+                "Invalid pattern ({pattern}) on line {line} of {}",
+                path.to_string_lossy()
+            ),
+            Self::ReadPatternFile { path, .. } => {
+                write!(
+                    f,
+                    "Failed to read pattern file ({})",
+                    path.to_string_lossy()
+                )
+            }
+            Self::InvalidRegex { pattern, .. } => write!(f, "Invalid regex ({pattern})"),
+            Self::InvalidOptions(message) => write!(f, "Invalid options: {message}"),
+            Self::ReadDir { path, .. } => {
+                write!(f, "Failed to read directory ({})", path.to_string_lossy())
+            }
+            Self::ReadLink { path, .. } => {
+                write!(f, "Failed to read link ({})", path.to_string_lossy())
+            }
+            Self::RecursiveLink { path } => write!(
+                f,
+                "Not following recursive link ({})",
+                path.to_string_lossy()
+            ),
+            Self::Canonicalize { path, .. } => write!(
+                f,
+                "Failed to canonicalize directory ({})",
+                path.to_string_lossy()
+            ),
+            Self::LoadIgnoreFile { path, .. } => {
+                write!(f, "Failed to load {}", path.to_string_lossy())
+            }
+            Self::LoadGlobalIgnoreFile(_) => write!(f, "Failed to load global gitignore file"),
+            Self::Write(_) => write!(f, "Failed to write output"),
+            Self::WriteFile { path, .. } => {
+                write!(f, "Failed to write {}", path.to_string_lossy())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidPattern { source, .. } | Self::InvalidPatternFile { source, .. } => {
+                Some(source)
+            }
+            Self::InvalidRegex { source, .. } => Some(source),
+            Self::InvalidOptions(_) | Self::RecursiveLink { .. } => None,
+            Self::ReadDir { source, .. }
+            | Self::ReadLink { source, .. }
+            | Self::Canonicalize { source, .. }
+            | Self::ReadPatternFile { source, .. }
+            | Self::WriteFile { source, .. } => Some(source),
+            Self::LoadIgnoreFile { source, .. } | Self::LoadGlobalIgnoreFile(source) => {
+                Some(source)
+            }
+            Self::Write(source) => Some(source),
+        }
+    }
+}
@@ -1,6 +1,16 @@
-use std::{ffi::OsStr, path::Path};
-
-use crate::{entry::Entry, ignore::IgnoreDir, options::TreeOptions};
+use std::{
+    os::unix::ffi::OsStrExt as _,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    entry::{Entry, FileKind},
+    error::Result,
+    gitignore::CompatIgnoreDir,
+    ignore::IgnoreDir,
+    options::TreeOptions,
+};
 
 pub struct FilteredEntry {
     pub filter_state: FilterState,
@@ -49,17 +59,34 @@ pub struct FilterState {
 
 pub struct TreeFilter<'filter> {
     state: FilterState,
-    ignore_dir: Option<IgnoreDir<'filter>>,
+    /// The root of the tree, which patterns containing a slash are matched
+    /// relative to.
+    root: Rc<PathBuf>,
+    ignore_dir: Option<IgnoreRules<'filter>>,
+}
+
+/// The rules from .gitignore files, which are implemented in the same way as
+/// the reference implementation in compat mode.
+enum IgnoreRules<'filter> {
+    Riptree(IgnoreDir<'filter>),
+    Compat(CompatIgnoreDir<'filter>),
 }
 
 impl<'filter> TreeFilter<'filter> {
-    pub(crate) fn new(dir: &Path, options: &TreeOptions) -> anyhow::Result<Self> {
+    pub(crate) fn new(dir: &Path, options: &TreeOptions) -> Result<Self> {
         Ok(Self {
             state: FilterState::default(),
-            ignore_dir: if options.respect_gitignore {
-                Some(IgnoreDir::new(dir)?)
-            } else {
-                None
+            root: Rc::new(dir.to_path_buf()),
+            ignore_dir: match options.respect_gitignore {
+                false => None,
+                true if options.compat => Some(IgnoreRules::Compat(CompatIgnoreDir::new(
+                    dir,
+                    &options.gitignore_files,
+                )?)),
+                true => Some(IgnoreRules::Riptree(IgnoreDir::new(
+                    dir,
+                    &options.gitignore_files,
+                )?)),
             },
         })
     }
@@ -69,37 +96,61 @@ impl<'filter> TreeFilter<'filter> {
         dir: &Entry,
         _options: &TreeOptions,
         state: FilterState,
-    ) -> anyhow::Result<Self> {
-        if let Some(ignore_dir) = &self.ignore_dir {
-            Ok(Self {
-                state,
-                ignore_dir: Some(ignore_dir.enter_dir(dir.path())?),
-            })
-        } else {
-            Ok(Self {
-                state,
-                ignore_dir: None,
-            })
-        }
+    ) -> Result<Self> {
+        Ok(Self {
+            state,
+            root: self.root.clone(),
+            ignore_dir: match &self.ignore_dir {
+                Some(IgnoreRules::Riptree(ignore_dir)) => {
+                    Some(IgnoreRules::Riptree(ignore_dir.enter_dir(dir.path())?))
+                }
+                Some(IgnoreRules::Compat(ignore_dir)) => {
+                    Some(IgnoreRules::Compat(ignore_dir.enter_dir(dir)?))
+                }
+                None => None,
+            },
+        })
     }
 
+    /// Returns the path of the entry relative to the root of the tree.
     #[inline]
-    fn file_name_included_by_pattern(&self, file_name: &OsStr, options: &TreeOptions) -> bool {
-        if let Some(file_include_globset) = options.file_include_globset.as_ref() {
-            if !file_include_globset.is_match(file_name) {
-                return false;
-            }
+    fn relative_path<'entry>(&self, entry: &'entry Entry) -> &'entry Path {
+        entry
+            .path()
+            .strip_prefix(self.root.as_path())
+            .unwrap_or(entry.path())
+    }
+
+    #[inline]
+    fn entry_included_by_pattern(
+        &self,
+        entry: &Entry,
+        is_dir: bool,
+        options: &TreeOptions,
+    ) -> bool {
+        if let Some(file_include_patterns) = options.file_include_patterns.as_ref()
+            && !file_include_patterns.is_match(entry.file_name(), self.relative_path(entry), is_dir)
+            && !(is_dir
+                && file_include_patterns
+                    .may_match_below(entry.file_name(), self.relative_path(entry)))
+        {
+            return false;
         }
 
         true
     }
 
     #[inline]
-    fn file_name_excluded_by_pattern(&self, file_name: &OsStr, options: &TreeOptions) -> bool {
-        if let Some(file_exclude_globset) = options.file_exclude_globset.as_ref() {
-            if file_exclude_globset.is_match(file_name) {
-                return true;
-            }
+    fn entry_excluded_by_pattern(
+        &self,
+        entry: &Entry,
+        is_dir: bool,
+        options: &TreeOptions,
+    ) -> bool {
+        if let Some(file_exclude_patterns) = options.file_exclude_patterns.as_ref()
+            && file_exclude_patterns.is_match(entry.file_name(), self.relative_path(entry), is_dir)
+        {
+            return true;
         }
 
         false
@@ -111,28 +162,15 @@ impl<'filter> TreeFilter<'filter> {
         }
 
         let mut filter_state = self.state.clone();
+        let is_dir = is_filtered_as_dir(&entry, options);
 
-        if entry.file_type().is_file() {
-            if options.list_directories_only {
-                return None;
-            }
-
-            if self.state.matched_dir_depth != 1
-                && !self.file_name_included_by_pattern(entry.file_name(), options)
-            {
-                return None;
-            }
-
-            if self.file_name_excluded_by_pattern(entry.file_name(), options) {
-                return None;
-            }
-        } else if entry.file_type().is_dir() {
+        if is_dir {
             if options.compat {
                 let mut matched_dir = false;
 
                 if (self.state.matched_dir_depth == 0)
                     && options.match_dirs
-                    && self.file_name_included_by_pattern(entry.file_name(), options)
+                    && self.entry_included_by_pattern(&entry, true, options)
                 {
                     matched_dir = true;
                 }
@@ -140,21 +178,47 @@ impl<'filter> TreeFilter<'filter> {
                 if filter_state.matched_dir_depth == 1 || matched_dir {
                     filter_state.matched_dir_depth += 1;
                 }
-            } else if !self.file_name_included_by_pattern(entry.file_name(), options) {
+            } else if !self.entry_included_by_pattern(&entry, true, options) {
+                return None;
+            }
+
+            if self.entry_excluded_by_pattern(&entry, true, options) {
+                return None;
+            }
+        } else {
+            // Symlinks to directories are listed with -d, even if they are not
+            // followed
+            if options.list_directories_only && !entry.target_is_dir() {
                 return None;
             }
 
-            if self.file_name_excluded_by_pattern(entry.file_name(), options) {
+            if self.state.matched_dir_depth != 1
+                && !self.entry_included_by_pattern(&entry, false, options)
+            {
+                return None;
+            }
+
+            if self.entry_excluded_by_pattern(&entry, false, options) {
+                return None;
+            }
+
+            // Directories are listed regardless of the predicates, so that
+            // their contents can be (see Tree::should_prune).
+            if !options.predicates.matches(&entry) {
                 return None;
             }
         }
 
-        if !self
-            .ignore_dir
-            .as_ref()
-            .map(|ignore_dir| ignore_dir.include(entry.path(), entry.file_type().is_dir()))
-            .unwrap_or(true)
-        {
+        let included_by_ignore_rules = match &self.ignore_dir {
+            Some(IgnoreRules::Riptree(ignore_dir)) => {
+                ignore_dir.include(entry.path(), entry.file_type().is_dir())
+            }
+            Some(IgnoreRules::Compat(ignore_dir)) => {
+                ignore_dir.include(entry.file_name().as_bytes(), is_dir)
+            }
+            None => true,
+        };
+        if !included_by_ignore_rules {
             return None;
         }
 
@@ -164,3 +228,21 @@ impl<'filter> TreeFilter<'filter> {
         })
     }
 }
+
+/// Whether the entry is filtered as a directory, rather than as a file. Like
+/// the reference implementation, symlinks to directories are only filtered as
+/// directories when they are followed (-l), and all other kinds of file are
+/// filtered as files.
+#[inline]
+fn is_filtered_as_dir(entry: &Entry, options: &TreeOptions) -> bool {
+    match entry.kind() {
+        FileKind::Directory => true,
+        FileKind::Symlink => options.follow_links && entry.target_is_dir(),
+        FileKind::File
+        | FileKind::Fifo
+        | FileKind::Socket
+        | FileKind::BlockDevice
+        | FileKind::CharDevice
+        | FileKind::Unknown => false,
+    }
+}
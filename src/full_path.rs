@@ -0,0 +1,118 @@
+use std::{
+    borrow::Cow,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::error::{Error, Result};
+
+/// How the paths printed for each entry (-f) are formed.
+///
+/// Each mode other than [`FullPath::AsGiven`] rewrites the root of each tree,
+/// and the path printed for each entry is the rewritten root joined with the
+/// names of the directories leading to it. Paths below symlinks which are
+/// followed (-l) therefore go through the symlink, rather than being resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum FullPath {
+    /// The root as it was given, like the reference implementation (-f).
+    #[default]
+    AsGiven,
+    /// The canonical, absolute path of the root.
+    Absolute,
+    /// The path of the root relative to the current directory.
+    RelativeToCurrentDir,
+    /// The path of the root relative to the given directory.
+    RelativeTo(PathBuf),
+}
+
+impl FullPath {
+    /// Returns the path which is printed for the given root. Roots which don't
+    /// exist are returned as given.
+    pub fn resolve_root(&self, root: &Path) -> Result<PathBuf> {
+        let canonicalized_root = match self {
+            Self::AsGiven => return Ok(root.to_path_buf()),
+            _ => match root.canonicalize() {
+                Ok(canonicalized_root) => canonicalized_root,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    return Ok(root.to_path_buf());
+                }
+                Err(source) => {
+                    return Err(Error::Canonicalize {
+                        path: root.to_path_buf(),
+                        source,
+                    });
+                }
+            },
+        };
+
+        let base = match self {
+            Self::AsGiven | Self::Absolute => return Ok(canonicalized_root),
+            Self::RelativeToCurrentDir => Path::new("."),
+            Self::RelativeTo(base) => base.as_path(),
+        };
+        let canonicalized_base = base.canonicalize().map_err(|source| Error::Canonicalize {
+            path: base.to_path_buf(),
+            source,
+        })?;
+
+        Ok(relative_path(&canonicalized_root, &canonicalized_base))
+    }
+}
+
+/// Maps the paths of the entries of a tree to the paths which are printed.
+pub(crate) struct PathDisplay {
+    root: PathBuf,
+    display_root: PathBuf,
+}
+
+impl PathDisplay {
+    pub(crate) fn new(full_path: &FullPath, root: &Path) -> Result<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            display_root: full_path.resolve_root(root)?,
+        })
+    }
+
+    /// The path which is printed for the root.
+    #[inline]
+    pub(crate) fn display_root(&self) -> &Path {
+        &self.display_root
+    }
+
+    /// The path which is printed for an entry below the root.
+    #[inline]
+    pub(crate) fn path<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        if self.root == self.display_root {
+            return Cow::Borrowed(path);
+        }
+
+        match path.strip_prefix(&self.root) {
+            Ok(relative) => Cow::Owned(self.display_root.join(relative)),
+            Err(_) => Cow::Borrowed(path),
+        }
+    }
+}
+
+/// Returns the path of `path` relative to `base`. Both paths must be absolute
+/// and canonical.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+
+    while let (Some(a), Some(b)) = (path_components.peek(), base_components.peek())
+        && a == b
+    {
+        path_components.next();
+        base_components.next();
+    }
+
+    let mut relative = base_components
+        .map(|_| Component::ParentDir)
+        .chain(path_components)
+        .collect::<PathBuf>();
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+
+    relative
+}
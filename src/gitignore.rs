@@ -0,0 +1,209 @@
+//! The reference implementation's own support for .gitignore files, which is
+//! used for --gitignore in compat mode. Unlike the `ignore` crate (see
+//! [`IgnoreDir`](crate::ignore::IgnoreDir)), it does not read global excludes
+//! or the ignore files of the parents of the root, and a negated pattern
+//! re-includes an entry regardless of where it appears.
+
+use std::{
+    os::unix::ffi::OsStrExt as _,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    entry::Entry,
+    error::{Error, Result},
+    pattern::patmatch,
+};
+
+/// The ignore files which apply to the entries of a directory.
+pub(crate) struct CompatIgnoreDir<'ignore> {
+    parent: Option<&'ignore CompatIgnoreDir<'ignore>>,
+    /// The canonical path of the directory.
+    dir: PathBuf,
+    files: Vec<IgnoreFile>,
+}
+
+impl<'ignore> CompatIgnoreDir<'ignore> {
+    /// Loads the .gitignore file of the root directory, and the files passed
+    /// to --gitfile.
+    pub(crate) fn new(dir: &Path, gitfiles: &[PathBuf]) -> Result<Self> {
+        // A missing root has no ignore files. The error is reported when the
+        // root is read.
+        let dir = match dir.canonicalize() {
+            Ok(dir) => dir,
+            Err(source) if source.kind() == std::io::ErrorKind::NotFound => dir.to_path_buf(),
+            Err(source) => {
+                return Err(Error::Canonicalize {
+                    path: dir.to_path_buf(),
+                    source,
+                });
+            }
+        };
+
+        // Like git's core.excludesFile, anchored patterns are relative to the
+        // root, rather than the directory of the file
+        let mut files = gitfiles
+            .iter()
+            .map(|path| IgnoreFile::load(path, &dir))
+            .collect::<Result<Vec<_>>>()?;
+        files.extend(IgnoreFile::load_dir(&dir)?);
+
+        Ok(Self {
+            parent: None,
+            dir,
+            files,
+        })
+    }
+
+    pub(crate) fn enter_dir(&'ignore self, dir: &Entry) -> Result<Self> {
+        let dir = if dir.file_type().is_symlink() {
+            canonicalize(dir.path())?
+        } else {
+            self.dir.join(dir.file_name())
+        };
+
+        Ok(Self {
+            parent: Some(self),
+            files: IgnoreFile::load_dir(&dir)?.into_iter().collect(),
+            dir,
+        })
+    }
+
+    /// Whether the entry with the given name in this directory is listed. It
+    /// is not listed if any pattern in any of the ignore files matches it,
+    /// unless any negated pattern also matches it.
+    pub(crate) fn include(&self, name: &[u8], is_dir: bool) -> bool {
+        let mut path = self.dir.as_os_str().as_bytes().to_vec();
+        path.push(b'/');
+        path.extend_from_slice(name);
+
+        let ignored = self
+            .ignore_files()
+            .any(|file| file.matches(&file.remove, &path, name, is_dir));
+
+        !ignored
+            || self
+                .ignore_files()
+                .any(|file| file.matches(&file.reverse, &path, name, is_dir))
+    }
+
+    /// Returns the ignore files of this directory and its ancestors.
+    fn ignore_files(&self) -> impl Iterator<Item = &IgnoreFile> {
+        std::iter::successors(Some(self), |dir| dir.parent).flat_map(|dir| &dir.files)
+    }
+}
+
+struct IgnoreFile {
+    /// The canonical path of the directory which anchored patterns are
+    /// relative to, followed by a slash.
+    base: Vec<u8>,
+    /// Patterns which ignore entries.
+    remove: Vec<IgnorePattern>,
+    /// Negated patterns, which re-include entries.
+    reverse: Vec<IgnorePattern>,
+}
+
+struct IgnorePattern {
+    pattern: Vec<u8>,
+    /// Whether the pattern is matched against the file name, rather than the
+    /// path of the entry. Patterns are anchored if they contain a slash other
+    /// than a trailing one.
+    relative: bool,
+}
+
+impl IgnoreFile {
+    /// Loads the .gitignore file in the given directory, if there is one.
+    fn load_dir(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(".gitignore");
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::load(&path, dir).map(Some)
+    }
+
+    /// Loads an ignore file whose anchored patterns are relative to `dir`.
+    fn load(path: &Path, dir: &Path) -> Result<Self> {
+        let contents = std::fs::read(path).map_err(|source| Error::LoadIgnoreFile {
+            path: path.to_path_buf(),
+            source: ignore::Error::Io(source),
+        })?;
+
+        let mut base = dir.as_os_str().as_bytes().to_vec();
+        base.push(b'/');
+        let mut file = Self {
+            base,
+            remove: Vec::new(),
+            reverse: Vec::new(),
+        };
+
+        for line in contents.split(|&byte| byte == b'\n') {
+            if line.first() == Some(&b'#') {
+                continue;
+            }
+            let reverse = line.first() == Some(&b'!');
+            let line = gittrim(line);
+            if line.is_empty() {
+                continue;
+            }
+
+            let pattern = if reverse { &line[1..] } else { &line[..] };
+            let slash = pattern.iter().position(|&byte| byte == b'/');
+            let pattern = IgnorePattern {
+                relative: slash.is_none_or(|slash| slash + 1 == pattern.len()),
+                pattern: pattern.strip_prefix(b"/").unwrap_or(pattern).to_vec(),
+            };
+            if reverse {
+                file.reverse.push(pattern);
+            } else {
+                file.remove.push(pattern);
+            }
+        }
+
+        Ok(file)
+    }
+
+    fn matches(&self, patterns: &[IgnorePattern], path: &[u8], name: &[u8], is_dir: bool) -> bool {
+        patterns.iter().any(|pattern| {
+            if pattern.relative {
+                return patmatch(name, &pattern.pattern, is_dir, false) == 1;
+            }
+            let mut anchored = self.base.clone();
+            anchored.extend_from_slice(&pattern.pattern);
+            patmatch(path, &anchored, is_dir, false) == 1
+        })
+    }
+}
+
+/// A port of `gittrim` from the reference implementation, which removes
+/// trailing spaces (even if they are escaped), and then all backslashes which
+/// escape another character.
+fn gittrim(line: &[u8]) -> Vec<u8> {
+    let mut end = line.len();
+    while end > 0 && line[end - 1] == b' ' {
+        end -= 1;
+        if end > 0 && line[end - 1] == b'\\' {
+            end -= 1;
+        }
+    }
+
+    let mut trimmed = Vec::with_capacity(end);
+    let mut bytes = line[..end].iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'\\' {
+            match bytes.next() {
+                Some(&escaped) => trimmed.push(escaped),
+                None => break,
+            }
+        } else {
+            trimmed.push(byte);
+        }
+    }
+    trimmed
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf> {
+    path.canonicalize().map_err(|source| Error::Canonicalize {
+        path: path.to_path_buf(),
+        source,
+    })
+}
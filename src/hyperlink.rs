@@ -0,0 +1,100 @@
+use std::{ffi::CStr, io::Write, os::unix::ffi::OsStrExt as _, path::Path};
+
+/// When names are written as hyperlinks (--hyperlink).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HyperlinkMode {
+    /// Only when writing to a terminal.
+    Auto,
+    Always,
+    #[default]
+    Never,
+}
+
+impl HyperlinkMode {
+    /// Returns the mode with the given name, as accepted by --hyperlink.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+}
+
+/// Writes names as OSC 8 terminal hyperlinks to the files they name, i.e.
+/// `file://hostname/absolute/path` URLs.
+#[derive(Debug, Clone)]
+pub struct Hyperlinks {
+    /// The percent-encoded hostname.
+    hostname: String,
+}
+
+impl Hyperlinks {
+    /// Creates hyperlinks to files on the given host.
+    pub fn new(hostname: &str) -> Self {
+        let mut encoded = String::new();
+        percent_encode(&mut encoded, hostname.as_bytes());
+        Self { hostname: encoded }
+    }
+
+    /// Decides whether to write hyperlinks, given whether the output is
+    /// written to a terminal, and if so, returns hyperlinks to files on this
+    /// host.
+    pub fn from_mode(mode: HyperlinkMode, is_terminal: bool) -> Option<Self> {
+        match mode {
+            HyperlinkMode::Never => return None,
+            HyperlinkMode::Auto if !is_terminal => return None,
+            HyperlinkMode::Auto | HyperlinkMode::Always => {}
+        }
+
+        Some(Self::new(&hostname().unwrap_or_default()))
+    }
+
+    /// Returns the URL of the file at `path`. Relative paths are made absolute
+    /// using the current directory.
+    pub fn url(&self, path: &Path) -> String {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut url = format!("file://{}", self.hostname);
+        percent_encode(&mut url, path.as_os_str().as_bytes());
+        url
+    }
+
+    /// Writes the escape sequence which starts a hyperlink to `path`.
+    #[inline]
+    pub(crate) fn write_start(&self, w: &mut impl Write, path: &Path) -> std::io::Result<()> {
+        write!(w, "\x1b]8;;{}\x1b\\", self.url(path))
+    }
+
+    /// Writes the escape sequence which ends a hyperlink.
+    #[inline]
+    pub(crate) fn write_end(&self, w: &mut impl Write) -> std::io::Result<()> {
+        write!(w, "\x1b]8;;\x1b\\")
+    }
+}
+
+/// Appends `bytes` to `url`, percent-encoding all bytes other than the
+/// unreserved characters of RFC 3986 and `/`.
+pub(crate) fn percent_encode(url: &mut String, bytes: &[u8]) {
+    for &byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{byte:02X}")),
+        }
+    }
+}
+
+/// Returns the hostname of this machine.
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: The buffer is valid for writes of its length
+    let result = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if result != 0 {
+        return None;
+    }
+
+    let hostname = CStr::from_bytes_until_nul(&buf).ok()?;
+    Some(hostname.to_string_lossy().into_owned())
+}
@@ -1,55 +1,57 @@
-use std::path::{Path, PathBuf};
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use anyhow::Context;
 use ignore::gitignore::{Gitignore, GitignoreBuilder};
 
+use crate::error::{Error, Result};
+
 pub struct IgnoreDir<'ignore> {
     parent: Option<&'ignore IgnoreDir<'ignore>>,
+    /// The root of the tree as it was given, and its canonical path. The
+    /// paths of entries are matched below the canonical path, which is the
+    /// base of the ignore files.
+    root: Rc<(PathBuf, PathBuf)>,
     gitignores: Vec<Gitignore>,
 }
 
 impl<'ignore> IgnoreDir<'ignore> {
-    pub(crate) fn new(dir: &Path) -> anyhow::Result<Self> {
-        let global_gitignore = match GitignoreBuilder::new("").build_global() {
-            (_, Some(err)) => panic!("{err}"),
-            (gitignore, None) => gitignore,
+    /// Loads the ignore files which apply to the given root directory, i.e.
+    /// the global excludes, the given files (--gitfile), and the .gitignore
+    /// files of the directory and its ancestors.
+    pub(crate) fn new(dir: &Path, gitfiles: &[PathBuf]) -> Result<Self> {
+        let canonicalized_root = match dir.canonicalize() {
+            Ok(canonicalized_root) => Some(canonicalized_root),
+            // A missing root has no ignore files. The error is reported when
+            // the root is read.
+            Err(source) if source.kind() == std::io::ErrorKind::NotFound => None,
+            Err(source) => {
+                return Err(Error::Canonicalize {
+                    path: dir.to_path_buf(),
+                    source,
+                });
+            }
         };
-        let mut gitignores = vec![global_gitignore];
-
-        let canonicalized_root = dir.canonicalize().with_context(|| {
-            format!(
-                "Failed to canonicalize directory ({})",
-                dir.to_string_lossy(),
-            )
-        })?;
-        let mut path_components = canonicalized_root.components();
+        let base = canonicalized_root.as_deref().unwrap_or(dir);
 
-        let mut current_dir = if let Some(root) = path_components.next() {
-            PathBuf::from(root.as_os_str())
-        } else {
-            return Ok(Self {
-                parent: None,
-                gitignores,
-            });
+        let global_gitignore = match GitignoreBuilder::new(base).build_global() {
+            (_, Some(source)) => return Err(Error::LoadGlobalIgnoreFile(source)),
+            (gitignore, None) => gitignore,
         };
+        let mut gitignores = vec![global_gitignore];
+        for gitfile in gitfiles {
+            gitignores.push(load_gitfile(gitfile, base)?);
+        }
 
-        for path_component in path_components {
-            current_dir = current_dir.join(path_component.as_os_str());
-            let mut builder = GitignoreBuilder::new(&current_dir);
-            let gitignore_path = current_dir.join(".gitignore");
-            if gitignore_path.exists() {
-                if let Some(err) = builder.add(&gitignore_path) {
-                    return Err(err).context(format!(
-                        "Failed to load {}",
-                        gitignore_path.to_string_lossy()
-                    ));
-                }
+        let mut path_components = canonicalized_root.iter().flat_map(|root| root.components());
+        if let Some(root) = path_components.next() {
+            let mut current_dir = PathBuf::from(root.as_os_str());
+            for path_component in path_components {
+                current_dir = current_dir.join(path_component.as_os_str());
+                gitignores.push(load_gitignore(&current_dir)?);
             }
-            gitignores.push(
-                builder.build().with_context(|| {
-                    format!("Failed to load {}", gitignore_path.to_string_lossy())
-                })?,
-            );
         }
 
         // Reverse so the highest priority .gitignore is first
@@ -57,39 +59,45 @@ impl<'ignore> IgnoreDir<'ignore> {
 
         Ok(Self {
             parent: None,
+            root: Rc::new((dir.to_path_buf(), base.to_path_buf())),
             gitignores,
         })
     }
 
-    pub(crate) fn enter_dir(&'ignore self, dir: &Path) -> anyhow::Result<Self> {
-        let gitignore_path = dir.join(".gitignore");
-        let gitignores =
-            if gitignore_path.exists() {
-                let mut builder = GitignoreBuilder::new(dir);
-                if let Some(err) = builder.add(&gitignore_path) {
-                    return Err(err).context(format!(
-                        "Failed to load {}",
-                        gitignore_path.to_string_lossy()
-                    ));
-                }
-                vec![builder.build().with_context(|| {
-                    format!("Failed to load {}", gitignore_path.to_string_lossy())
-                })?]
-            } else {
-                vec![]
-            };
+    pub(crate) fn enter_dir(&'ignore self, dir: &Path) -> Result<Self> {
+        let gitignores = if dir.join(".gitignore").exists() {
+            vec![load_gitignore(&self.canonical_path(dir))?]
+        } else {
+            vec![]
+        };
 
         Ok(Self {
             parent: Some(self),
+            root: self.root.clone(),
             gitignores,
         })
     }
 
+    /// Returns the path below the canonical root of the entry at `path`, which
+    /// is below the root as it was given.
+    #[inline]
+    fn canonical_path<'path>(&self, path: &'path Path) -> Cow<'path, Path> {
+        let (root, base) = &*self.root;
+        match path.strip_prefix(root) {
+            Ok(relative_path) => Cow::Owned(base.join(relative_path)),
+            Err(_) => Cow::Borrowed(path),
+        }
+    }
+
     pub(crate) fn include(&self, path: &Path, is_dir: bool) -> bool {
         if is_dir && path.file_name().map(|name| name == ".git").unwrap_or(false) {
             return false;
         }
 
+        self.include_canonical(&self.canonical_path(path), is_dir)
+    }
+
+    fn include_canonical(&self, path: &Path, is_dir: bool) -> bool {
         for gitignore in &self.gitignores {
             let is_match = gitignore.matched(path, is_dir);
 
@@ -103,9 +111,44 @@ impl<'ignore> IgnoreDir<'ignore> {
         }
 
         if let Some(parent) = self.parent {
-            parent.include(path, is_dir)
+            parent.include_canonical(path, is_dir)
         } else {
             true
         }
     }
 }
+
+/// Loads an ignore file passed to --gitfile. Like git's core.excludesFile,
+/// anchored patterns are relative to the root, i.e. `base`.
+fn load_gitfile(path: &Path, base: &Path) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(base);
+    if let Some(source) = builder.add(path) {
+        return Err(Error::LoadIgnoreFile {
+            path: path.to_path_buf(),
+            source,
+        });
+    }
+    builder.build().map_err(|source| Error::LoadIgnoreFile {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Loads the .gitignore file in the given directory. If the directory does not
+/// contain a .gitignore file, an empty matcher is returned.
+fn load_gitignore(dir: &Path) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(dir);
+    let gitignore_path = dir.join(".gitignore");
+    if gitignore_path.exists()
+        && let Some(source) = builder.add(&gitignore_path)
+    {
+        return Err(Error::LoadIgnoreFile {
+            path: gitignore_path,
+            source,
+        });
+    }
+    builder.build().map_err(|source| Error::LoadIgnoreFile {
+        path: gitignore_path,
+        source,
+    })
+}
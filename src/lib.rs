@@ -1,9 +1,56 @@
+//! riptree2 is a Rust rewrite of the Unix tree command. This crate can also be
+//! used as a library to list directory trees.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//!
+//! use riptree2::{options::TreeOptions, stats::DefaultTreeStats, tree::Tree};
+//!
+//! # fn main() -> riptree2::error::Result<()> {
+//! let options = Arc::new(TreeOptions::builder().max_level(2).icons(false).build()?);
+//! let tree = Tree::new(".".into(), options.clone())?;
+//! let mut stats = DefaultTreeStats::new(options);
+//!
+//! let mut output = Vec::new();
+//! tree.write_root(&mut output)?;
+//! tree.write(&mut output, &mut stats)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Alternatively, [`Tree::build`](tree::Tree::build) returns an in-memory
+//! [`Node`](model::Node) tree which can be inspected or modified before being
+//! rendered with one of the renderers in [`render`]. For large trees,
+//! [`Tree::visit`](tree::Tree::visit) streams the entries to a
+//! [`TreeVisitor`](visitor::TreeVisitor) without keeping them in memory.
+//!
+//! # Features
+//!
+//! - `cli` (default): command line argument parsing and config files. Disable
+//!   default features to use the library without depending on clap.
+
+#[cfg(feature = "cli")]
 pub mod args;
+pub mod charset;
+pub mod colors;
+#[cfg(feature = "cli")]
+pub mod config;
 pub mod entry;
+pub mod error;
 pub mod filter;
+pub mod full_path;
+mod gitignore;
+pub mod hyperlink;
 pub mod icons;
 pub mod ignore;
+pub mod model;
+pub mod name;
 pub mod options;
+mod pattern;
+pub mod predicate;
+pub mod render;
+pub mod size;
 pub mod sorter;
 pub mod stats;
 pub mod tree;
+pub mod visitor;
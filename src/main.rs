@@ -1,18 +1,26 @@
-use std::{io::Write, sync::Arc};
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::Context as _;
-use clap::Parser as _;
 use riptree2::{
-    args::TreeArgs,
+    config::parse_args,
     options::TreeOptions,
-    stats::{DefaultTreeStats, NoopTreeStats, TreeStats},
+    render::{html::write_html_page, json::JsonRenderer},
+    stats::{DefaultTreeStats, TreeStats},
     tree::Tree,
 };
 
 fn main() -> anyhow::Result<()> {
-    let args = TreeArgs::parse();
+    let args = parse_args()?;
 
     let output_to_file = args.output_to_file.clone();
+    let atomic = args.atomic;
+    let compat = args.compat;
 
     let roots = if args.roots.is_empty() {
         vec![".".to_string()]
@@ -20,17 +28,206 @@ fn main() -> anyhow::Result<()> {
         args.roots.clone()
     };
 
-    let no_report = args.no_report;
+    let print_report = !args.no_report;
+    let format = if let Some(base_href) = args.html_base_href.clone() {
+        OutputFormat::Html {
+            base_href,
+            rerun: args.rerun_at_max_level,
+        }
+    } else if args.json {
+        OutputFormat::Json
+    } else if args.disk_usage {
+        OutputFormat::DiskUsage
+    } else {
+        OutputFormat::Text
+    };
+
+    // Like tree, which fails before listing anything
+    if compat && args.gitfile.iter().any(|path| File::open(path).is_err()) {
+        eprintln!("tree: Could not load gitignore file");
+        std::process::exit(1);
+    }
 
     let opts = Arc::new(TreeOptions::try_from(args).context("Failed to validate options")?);
 
-    if let Some(file) = output_to_file {
-        let mut writer = std::fs::File::create(file).unwrap();
-        print(&mut writer, !no_report, &roots, opts)
-    } else {
-        let mut writer = std::io::stdout();
-        print(&mut writer, !no_report, &roots, opts)
+    let errors = match output_to_file {
+        Some(file) if atomic => {
+            let path = Path::new(&file);
+            let temp_path = atomic_temp_path(path);
+            let result = File::create(&temp_path)
+                .with_context(|| {
+                    format!(
+                        "Failed to create temporary output file ({})",
+                        temp_path.to_string_lossy()
+                    )
+                })
+                .and_then(|temp_file| {
+                    let mut writer = BufWriter::new(temp_file);
+                    let errors = print_output(&mut writer, &format, print_report, &roots, opts)?;
+                    let temp_file = writer.into_inner().map_err(|err| err.into_error())?;
+                    temp_file.sync_all()?;
+                    Ok(errors)
+                })
+                .and_then(|errors| {
+                    std::fs::rename(&temp_path, path).with_context(|| {
+                        format!("Failed to replace output file ({})", path.to_string_lossy())
+                    })?;
+                    Ok(errors)
+                });
+            if result.is_err() {
+                let _ = std::fs::remove_file(&temp_path);
+            }
+            result?
+        }
+        Some(file) => {
+            let file_handle = match File::create(&file) {
+                Ok(file_handle) => file_handle,
+                // Like tree, which fails before listing anything
+                Err(_) if compat => {
+                    eprintln!("tree: invalid filename '{}'", file.to_string_lossy());
+                    std::process::exit(1);
+                }
+                Err(err) => {
+                    return Err(err).with_context(|| {
+                        format!("Failed to open output file ({})", file.to_string_lossy())
+                    });
+                }
+            };
+            let mut writer = BufWriter::new(file_handle);
+            let errors = print_output(&mut writer, &format, print_report, &roots, opts)?;
+            writer.flush()?;
+            errors
+        }
+        None => {
+            let mut writer = BufWriter::new(std::io::stdout().lock());
+            let errors = print_output(&mut writer, &format, print_report, &roots, opts);
+            writer.flush()?;
+            errors?
+        }
+    };
+
+    // Like tree, exit with status 2 if any directory could not be read.
+    if errors > 0 {
+        std::process::exit(2);
+    }
+
+    Ok(())
+}
+
+enum OutputFormat {
+    Text,
+    /// Text, with directory sizes computed from their contents (--du).
+    DiskUsage,
+    Json,
+    Html {
+        base_href: String,
+        rerun: bool,
+    },
+}
+
+/// Returns the path of the temporary file which is written with --atomic, and
+/// then renamed to `path`. The file is created in the same directory, so that
+/// the rename doesn't cross file systems.
+#[inline]
+fn atomic_temp_path(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(file_name)
+}
+
+/// Prints the trees in the given format, and returns the number of
+/// directories which could not be read.
+#[inline]
+fn print_output(
+    writer: &mut impl Write,
+    format: &OutputFormat,
+    print_report: bool,
+    roots: &Vec<String>,
+    opts: Arc<TreeOptions>,
+) -> anyhow::Result<usize> {
+    match format {
+        OutputFormat::Text => print(writer, print_report, roots, opts),
+        OutputFormat::DiskUsage => print_disk_usage(writer, print_report, roots, opts),
+        OutputFormat::Json => print_json(writer, print_report, roots, opts),
+        OutputFormat::Html { base_href, rerun } => {
+            print_html(writer, print_report, roots, opts, base_href, *rerun)
+        }
+    }
+}
+
+#[inline]
+fn print_json(
+    writer: &mut impl Write,
+    print_report: bool,
+    roots: &[String],
+    opts: Arc<TreeOptions>,
+) -> anyhow::Result<usize> {
+    let nodes = roots
+        .iter()
+        .map(|root| Tree::new(root.clone().into(), opts.clone())?.build())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    JsonRenderer::new(opts.clone()).render(writer, &nodes, print_report)?;
+
+    let mut stats = DefaultTreeStats::new(opts);
+    for node in &nodes {
+        node.visit(&mut stats)?;
     }
+
+    Ok(stats.errors())
+}
+
+#[inline]
+fn print_html(
+    writer: &mut impl Write,
+    print_report: bool,
+    roots: &[String],
+    opts: Arc<TreeOptions>,
+    base_href: &str,
+    rerun: bool,
+) -> anyhow::Result<usize> {
+    let roots = roots.iter().map(Into::into).collect::<Vec<_>>();
+    // Like tree, pages which can't be written for -R are skipped
+    let mut page_errors = 0;
+    let stats = write_html_page(
+        writer,
+        &roots,
+        opts,
+        base_href,
+        rerun,
+        print_report,
+        &mut |err| {
+            eprintln!("Warning: {:#}", anyhow::Error::from(err));
+            page_errors += 1;
+        },
+    )?;
+
+    Ok(stats.errors() + page_errors)
+}
+
+/// Directory sizes (--du) are only known once their contents have been read,
+/// so the lines of each tree are buffered until it has been traversed.
+#[inline]
+fn print_disk_usage(
+    writer: &mut impl Write,
+    print_report: bool,
+    roots: &[String],
+    opts: Arc<TreeOptions>,
+) -> anyhow::Result<usize> {
+    let mut stats = DefaultTreeStats::new(opts.clone());
+    for root in roots {
+        let tree = Tree::new(root.into(), opts.clone())?;
+        let size = tree.write_disk_usage(writer, &mut stats)?;
+        stats.count_disk_usage(size);
+    }
+
+    if print_report {
+        writeln!(writer)?;
+        stats.write(writer)?;
+    }
+
+    Ok(stats.errors())
 }
 
 #[inline]
@@ -39,17 +236,16 @@ fn print(
     print_report: bool,
     roots: &Vec<String>,
     opts: Arc<TreeOptions>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<usize> {
+    let mut stats = DefaultTreeStats::new(opts.clone());
+    print_tree(writer, roots, opts.clone(), &mut stats)?;
+
     if print_report {
-        let mut stats = DefaultTreeStats::new(opts.clone());
-        print_tree(writer, roots, opts.clone(), &mut stats)?;
         writeln!(writer)?;
         stats.write(writer)?;
-    } else {
-        print_tree(writer, roots, opts.clone(), &mut NoopTreeStats)?;
     }
 
-    Ok(())
+    Ok(stats.errors())
 }
 
 #[inline]
@@ -61,7 +257,7 @@ fn print_tree(
 ) -> anyhow::Result<()> {
     for root in roots {
         let tree = Tree::new(root.clone().into(), opts.clone())?;
-        writeln!(writer, "{root}")?;
+        tree.write_root(writer)?;
         tree.write(writer, stats)?;
     }
 
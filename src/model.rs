@@ -0,0 +1,155 @@
+use std::{
+    ffi::OsStr,
+    fs::Metadata,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    entry::{Entry, FileKind},
+    error::{Error, Result},
+    filter::FilteredEntry,
+    visitor::TreeVisitor,
+};
+
+/// A node of an in-memory tree, built by [`Tree::build`](crate::tree::Tree::build).
+///
+/// The tree has already been filtered and sorted according to the
+/// [`TreeOptions`](crate::options::TreeOptions) it was built with, so it can be
+/// post-processed and then rendered with one of the renderers in
+/// [`render`](crate::render).
+pub struct Node {
+    pub(crate) entry: FilteredEntry,
+    pub(crate) link_target: Option<PathBuf>,
+    pub(crate) children: Option<Vec<Node>>,
+    /// Children which are not listed because of --max-entries.
+    pub(crate) elided: Vec<Node>,
+    /// The number of directories and files below the node, if it is a
+    /// directory which is not descended into because of -L
+    /// (--show-truncated).
+    pub(crate) truncated: Option<(usize, usize)>,
+    pub(crate) error: Option<Error>,
+}
+
+impl Node {
+    /// The file name of the node. For the root node, this is the path of the
+    /// root as it was given.
+    pub fn name(&self) -> &OsStr {
+        self.entry().file_name()
+    }
+
+    pub fn path(&self) -> &Path {
+        self.entry().path()
+    }
+
+    pub fn kind(&self) -> FileKind {
+        self.entry().kind()
+    }
+
+    /// The target of the node, if it is a symlink.
+    pub fn link_target(&self) -> Option<&Path> {
+        self.link_target.as_deref()
+    }
+
+    /// The metadata of the node, without following symlinks. The metadata is
+    /// fetched the first time it is requested, and `None` is returned if it
+    /// could not be read.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.entry().metadata()
+    }
+
+    /// The children of the node. This is `None` if the node is not a
+    /// directory, or if it was not descended into (e.g. because of -L).
+    pub fn children(&self) -> Option<&[Node]> {
+        self.children.as_deref()
+    }
+
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Node>> {
+        self.children.as_mut()
+    }
+
+    /// The children of the node which follow its listed children, but which
+    /// are not listed because of --max-entries.
+    pub fn elided(&self) -> &[Node] {
+        &self.elided
+    }
+
+    /// The number of directories and files below the node which would be
+    /// listed, if it is a directory which was not descended into because of -L.
+    /// Only counted with
+    /// [`show_truncated`](crate::options::TreeOptionsBuilder::show_truncated).
+    pub fn truncated(&self) -> Option<(usize, usize)> {
+        self.truncated
+    }
+
+    /// The error encountered while reading the node, if it is a directory
+    /// which could not be read. Such nodes have no children.
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    pub fn entry(&self) -> &Entry {
+        self.entry.as_ref()
+    }
+
+    /// Replays the descendants of the node to `visitor`, in the same way as
+    /// [`Tree::visit`](crate::tree::Tree::visit), e.g. to render a tree after
+    /// it has been modified.
+    pub fn visit(&self, visitor: &mut impl TreeVisitor) -> Result<()> {
+        if let Some(error) = self.error() {
+            visitor.error(self.path(), error, 0)?;
+        }
+        self.visit_children(visitor, 1)
+    }
+
+    /// Whether the node is a directory whose only child is a directory which
+    /// was descended into (see [`TreeVisitor::collapsed_dir`]).
+    #[inline]
+    fn is_collapsible(&self) -> bool {
+        !self.entry().file_type().is_symlink()
+            && self.elided.is_empty()
+            && matches!(
+                self.children(),
+                Some([child]) if child.children().is_some() || child.error().is_some()
+            )
+    }
+
+    fn visit_children(&self, visitor: &mut impl TreeVisitor, depth: usize) -> Result<()> {
+        let children = self.children().unwrap_or_default();
+
+        for (index, child) in children.iter().enumerate() {
+            let is_last = index + 1 == children.len() && self.elided.is_empty();
+            if let Some(error) = child.error() {
+                visitor.error(child.path(), error, depth)?;
+            }
+            if let Some((dirs, files)) = child.truncated() {
+                visitor.truncated(&child.entry, dirs, files, depth)?;
+            }
+
+            if child.is_collapsible() {
+                visitor.collapsed_dir(&child.entry, depth, is_last)?;
+                child.visit_children(visitor, depth + 1)?;
+                visitor.leave_dir(&child.entry, depth, is_last)?;
+                continue;
+            }
+
+            visitor.entry(&child.entry, child.link_target(), depth, is_last)?;
+
+            if child.children().is_some() {
+                visitor.enter_dir(&child.entry, depth, is_last)?;
+                child.visit_children(visitor, depth + 1)?;
+                visitor.leave_dir(&child.entry, depth, is_last)?;
+            }
+        }
+
+        if !self.elided.is_empty() {
+            let elided = self
+                .elided
+                .iter()
+                .map(|node| &node.entry)
+                .collect::<Vec<_>>();
+            visitor.elided(&elided, depth)?;
+        }
+
+        Ok(())
+    }
+}
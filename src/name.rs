@@ -0,0 +1,91 @@
+use std::io::Write;
+
+/// How non-printable characters in file names and link targets are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonPrintable {
+    /// Write non-printable characters as backslash escaped octal codes, e.g.
+    /// `\012` for a newline (default).
+    #[default]
+    Escape,
+    /// Write non-printable characters as `?` (-q).
+    Replace,
+    /// Write non-printable characters as is (-N).
+    Raw,
+}
+
+/// Controls how names are written, see [`write_name`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NameStyle {
+    pub(crate) non_printable: NonPrintable,
+    pub(crate) quote: bool,
+    /// Whether names are decoded as UTF-8. This corresponds to whether the
+    /// reference implementation runs with a multibyte locale.
+    pub(crate) utf8: bool,
+}
+
+/// Writes a file name or link target in the same way as the reference
+/// implementation (see `printit` in tree).
+///
+/// If the name is valid UTF-8 (and the locale is UTF-8), non-printable
+/// characters are escaped by code point. Otherwise the name is written byte by
+/// byte, and all non-ASCII bytes are treated as non-printable, except that -q
+/// writes them as is in a UTF-8 locale.
+pub(crate) fn write_name(w: &mut impl Write, name: &[u8], style: NameStyle) -> std::io::Result<()> {
+    if style.quote {
+        w.write_all(b"\"")?;
+    }
+
+    if style.non_printable == NonPrintable::Raw {
+        w.write_all(name)?;
+    } else if let Some(name) = std::str::from_utf8(name).ok().filter(|_| style.utf8) {
+        write_chars(w, name, style)?;
+    } else {
+        write_bytes(w, name, style)?;
+    }
+
+    if style.quote {
+        w.write_all(b"\"")?;
+    }
+
+    Ok(())
+}
+
+fn write_chars(w: &mut impl Write, name: &str, style: NameStyle) -> std::io::Result<()> {
+    let mut buf = [0; 4];
+
+    for c in name.chars() {
+        if !c.is_control() {
+            if style.quote && c == '"' {
+                w.write_all(b"\\")?;
+            }
+            w.write_all(c.encode_utf8(&mut buf).as_bytes())?;
+        } else if style.non_printable == NonPrintable::Replace {
+            w.write_all(b"?")?;
+        } else {
+            write!(w, "\\{:03o}", c as u32)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_bytes(w: &mut impl Write, name: &[u8], style: NameStyle) -> std::io::Result<()> {
+    for &byte in name {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            if style.quote && byte == b'"' {
+                w.write_all(b"\\")?;
+            }
+            w.write_all(&[byte])?;
+        } else if style.non_printable == NonPrintable::Replace {
+            if style.utf8 && !byte.is_ascii() {
+                w.write_all(&[byte])?;
+            } else {
+                w.write_all(b"?")?;
+            }
+        } else {
+            write!(w, "\\{byte:03o}")?;
+        }
+    }
+
+    Ok(())
+}
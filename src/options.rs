@@ -1,22 +1,75 @@
-use std::cmp::Ordering;
+#[cfg(feature = "cli")]
+use std::io::IsTerminal as _;
+use std::path::PathBuf;
 
-use anyhow::Context as _;
-use globset::{GlobBuilder, GlobSet};
-
-use crate::{args::TreeArgs, entry::Entry, sorter::default_sorter};
+#[cfg(feature = "cli")]
+use crate::{
+    args::TreeArgs, charset::locale_codeset_is_utf8, hyperlink::HyperlinkMode, sorter::SortType,
+};
+use crate::{
+    charset::Charset,
+    colors::Colors,
+    error::{Error, Result},
+    full_path::FullPath,
+    hyperlink::Hyperlinks,
+    name::{NameStyle, NonPrintable},
+    pattern::{PatternSet, read_pattern_file},
+    predicate::{FileTypeFilter, Predicates, SizeFilter, TimeFilter},
+    size::SizeUnits,
+    sorter::Sorter,
+};
 
+/// Options which control how a [`Tree`](crate::tree::Tree) is listed.
+///
+/// Use [`TreeOptions::builder`] to construct options other than the defaults.
 pub struct TreeOptions {
-    pub compat: bool,
-    pub show_hidden_files: bool,
-    pub list_directories_only: bool,
-    pub print_full_path_prefix: bool,
-    pub max_level: Option<usize>,
-    pub file_include_globset: Option<GlobSet>,
-    pub file_exclude_globset: Option<GlobSet>,
-    pub match_dirs: bool,
-    pub respect_gitignore: bool,
-    pub icons: bool,
-    pub sorter: fn(&Entry, &Entry) -> Ordering,
+    pub(crate) compat: bool,
+    pub(crate) show_hidden_files: bool,
+    pub(crate) list_directories_only: bool,
+    pub(crate) follow_links: bool,
+    pub(crate) print_full_path_prefix: bool,
+    pub(crate) full_path: FullPath,
+    pub(crate) max_level: Option<usize>,
+    pub(crate) file_include_patterns: Option<PatternSet>,
+    pub(crate) file_exclude_patterns: Option<PatternSet>,
+    pub(crate) match_dirs: bool,
+    pub(crate) respect_gitignore: bool,
+    pub(crate) gitignore_files: Vec<PathBuf>,
+    pub(crate) icons: bool,
+    pub(crate) charset: Charset,
+    pub(crate) colors: Option<Colors>,
+    pub(crate) hyperlinks: Option<Hyperlinks>,
+    pub(crate) non_printable: NonPrintable,
+    pub(crate) quote_names: bool,
+    pub(crate) utf8_names: bool,
+    pub(crate) classify: bool,
+    pub(crate) print_size: bool,
+    pub(crate) size_units: SizeUnits,
+    pub(crate) disk_usage: bool,
+    pub(crate) sorter: Sorter,
+    pub(crate) max_entries: Option<usize>,
+    pub(crate) count_elided_entries: bool,
+    pub(crate) show_truncated: bool,
+    pub(crate) count_truncated_entries: bool,
+    pub(crate) predicates: Predicates,
+    pub(crate) prune: bool,
+    pub(crate) collapse: bool,
+}
+
+impl TreeOptions {
+    /// Returns a builder, initialised with the default options.
+    pub fn builder() -> TreeOptionsBuilder {
+        TreeOptionsBuilder::default()
+    }
+
+    #[inline]
+    pub(crate) fn name_style(&self) -> NameStyle {
+        NameStyle {
+            non_printable: self.non_printable,
+            quote: self.quote_names,
+            utf8: self.utf8_names,
+        }
+    }
 }
 
 impl Default for TreeOptions {
@@ -25,76 +78,690 @@ impl Default for TreeOptions {
             compat: false,
             show_hidden_files: false,
             list_directories_only: false,
+            follow_links: false,
             print_full_path_prefix: false,
+            full_path: FullPath::AsGiven,
             max_level: None,
-            file_include_globset: None,
-            file_exclude_globset: None,
+            file_include_patterns: None,
+            file_exclude_patterns: None,
             match_dirs: false,
             respect_gitignore: true,
+            gitignore_files: Vec::new(),
             icons: true,
-            sorter: default_sorter,
+            charset: Charset::Utf8,
+            colors: None,
+            hyperlinks: None,
+            non_printable: NonPrintable::Escape,
+            quote_names: false,
+            utf8_names: true,
+            classify: false,
+            print_size: false,
+            size_units: SizeUnits::Bytes,
+            disk_usage: false,
+            sorter: Sorter::default(),
+            max_entries: None,
+            count_elided_entries: false,
+            show_truncated: false,
+            count_truncated_entries: false,
+            predicates: Predicates::default(),
+            prune: false,
+            collapse: false,
         }
     }
 }
 
+/// Builder for [`TreeOptions`]. Patterns are compiled, and combinations of
+/// options are validated, when [`TreeOptionsBuilder::build`] is called.
+///
+/// ```
+/// use riptree2::options::TreeOptions;
+///
+/// let options = TreeOptions::builder()
+///     .max_level(2)
+///     .file_exclude_patterns(["target", "*.lock"])
+///     .icons(false)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct TreeOptionsBuilder {
+    compat: bool,
+    show_hidden_files: bool,
+    list_directories_only: bool,
+    follow_links: bool,
+    print_full_path_prefix: bool,
+    full_path: FullPath,
+    max_level: Option<usize>,
+    file_include_patterns: Vec<String>,
+    file_exclude_patterns: Vec<String>,
+    file_include_pattern_files: Vec<PathBuf>,
+    file_exclude_pattern_files: Vec<PathBuf>,
+    file_include_regexes: Vec<String>,
+    file_exclude_regexes: Vec<String>,
+    ignore_case: bool,
+    match_dirs: bool,
+    respect_gitignore: bool,
+    gitignore_files: Vec<PathBuf>,
+    icons: bool,
+    charset: Charset,
+    colors: Option<Colors>,
+    hyperlinks: Option<Hyperlinks>,
+    non_printable: NonPrintable,
+    quote_names: bool,
+    utf8_names: bool,
+    classify: bool,
+    print_size: bool,
+    size_units: SizeUnits,
+    disk_usage: bool,
+    sorter: Sorter,
+    max_entries: Option<usize>,
+    count_elided_entries: bool,
+    show_truncated: bool,
+    count_truncated_entries: bool,
+    predicates: Predicates,
+    prune: bool,
+    collapse: bool,
+}
+
+impl Default for TreeOptionsBuilder {
+    fn default() -> Self {
+        Self {
+            compat: false,
+            show_hidden_files: false,
+            list_directories_only: false,
+            follow_links: false,
+            print_full_path_prefix: false,
+            full_path: FullPath::AsGiven,
+            max_level: None,
+            file_include_patterns: Vec::new(),
+            file_exclude_patterns: Vec::new(),
+            file_include_pattern_files: Vec::new(),
+            file_exclude_pattern_files: Vec::new(),
+            file_include_regexes: Vec::new(),
+            file_exclude_regexes: Vec::new(),
+            ignore_case: false,
+            match_dirs: false,
+            respect_gitignore: true,
+            gitignore_files: Vec::new(),
+            icons: true,
+            charset: Charset::Utf8,
+            colors: None,
+            hyperlinks: None,
+            non_printable: NonPrintable::Escape,
+            quote_names: false,
+            utf8_names: true,
+            classify: false,
+            print_size: false,
+            size_units: SizeUnits::Bytes,
+            disk_usage: false,
+            sorter: Sorter::default(),
+            max_entries: None,
+            count_elided_entries: false,
+            show_truncated: false,
+            count_truncated_entries: false,
+            predicates: Predicates::default(),
+            prune: false,
+            collapse: false,
+        }
+    }
+}
+
+impl TreeOptionsBuilder {
+    /// Reproduce the behaviour of the reference implementation exactly.
+    pub fn compat(mut self, compat: bool) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// List hidden files (-a).
+    pub fn show_hidden_files(mut self, show_hidden_files: bool) -> Self {
+        self.show_hidden_files = show_hidden_files;
+        self
+    }
+
+    /// List directories only (-d).
+    pub fn list_directories_only(mut self, list_directories_only: bool) -> Self {
+        self.list_directories_only = list_directories_only;
+        self
+    }
+
+    /// Follow symbolic links to directories as if they were directories (-l).
+    pub fn follow_links(mut self, follow_links: bool) -> Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    /// Print the full path prefix for each file (-f).
+    pub fn print_full_path_prefix(mut self, print_full_path_prefix: bool) -> Self {
+        self.print_full_path_prefix = print_full_path_prefix;
+        self
+    }
+
+    /// How the paths printed for each entry are formed. Modes other than
+    /// [`FullPath::AsGiven`] imply
+    /// [`print_full_path_prefix`](TreeOptionsBuilder::print_full_path_prefix),
+    /// and are not available in compat mode.
+    pub fn full_path(mut self, full_path: FullPath) -> Self {
+        self.full_path = full_path;
+        self
+    }
+
+    /// Descend only `max_level` directories deep (-L). Must be greater than 0.
+    pub fn max_level(mut self, max_level: usize) -> Self {
+        self.max_level = Some(max_level);
+        self
+    }
+
+    /// List only those files that match one of the patterns (-P). Outside of
+    /// compat mode, patterns starting with `!` are negated, and the last
+    /// pattern to match a file wins.
+    pub fn file_include_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.file_include_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Do not list files that match any of the patterns (-I). Outside of
+    /// compat mode, patterns starting with `!` are negated, and the last
+    /// pattern to match a file wins.
+    pub fn file_exclude_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.file_exclude_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// List only those files that match one of the patterns read from the
+    /// files (--include-from), in addition to the include patterns. Each file
+    /// contains one pattern per line, and may contain empty lines and comments
+    /// starting with `#`. The files are read when the options are built.
+    pub fn file_include_pattern_files<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.file_include_pattern_files = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Do not list files that match any of the patterns read from the files
+    /// (--exclude-from), in addition to the exclude patterns. See
+    /// [`TreeOptionsBuilder::file_include_pattern_files`] for the format.
+    pub fn file_exclude_pattern_files<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.file_exclude_pattern_files = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// List only those files whose name matches one of the regexes
+    /// (--regex-include), in addition to those matching the include patterns.
+    /// Regexes containing a slash are matched against the path relative to the
+    /// root. Not available in compat mode.
+    pub fn file_include_regexes<I, S>(mut self, regexes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.file_include_regexes = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Do not list files whose name matches any of the regexes
+    /// (--regex-exclude). Regexes containing a slash are matched against the
+    /// path relative to the root. Not available in compat mode.
+    pub fn file_exclude_regexes<I, S>(mut self, regexes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.file_exclude_regexes = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Ignore case when pattern matching (--ignore-case).
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Include directory names in include pattern matching (--matchdirs).
+    /// Requires compat mode.
+    pub fn match_dirs(mut self, match_dirs: bool) -> Self {
+        self.match_dirs = match_dirs;
+        self
+    }
+
+    /// Respect the filter rules from the given ignore files (--gitfile), in
+    /// addition to those from .gitignore files. Only used if
+    /// [`respect_gitignore`](TreeOptionsBuilder::respect_gitignore) is set.
+    pub fn gitignore_files<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.gitignore_files = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Respect filter rules from .gitignore files.
+    pub fn respect_gitignore(mut self, respect_gitignore: bool) -> Self {
+        self.respect_gitignore = respect_gitignore;
+        self
+    }
+
+    /// Show Nerd Fonts icons.
+    pub fn icons(mut self, icons: bool) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// The characters used to draw the lines of the tree.
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Colorize names using the given colors. Pass `None` to disable colors.
+    pub fn colors(mut self, colors: Option<Colors>) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Write names as hyperlinks to the files they name (--hyperlink). Pass
+    /// `None` to disable hyperlinks.
+    pub fn hyperlinks(mut self, hyperlinks: Option<Hyperlinks>) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// How non-printable characters in names are written (-q, -N).
+    pub fn non_printable(mut self, non_printable: NonPrintable) -> Self {
+        self.non_printable = non_printable;
+        self
+    }
+
+    /// Quote names in double quotes (-Q).
+    pub fn quote_names(mut self, quote_names: bool) -> Self {
+        self.quote_names = quote_names;
+        self
+    }
+
+    /// Decode names as UTF-8 when looking for non-printable characters. If
+    /// false, all non-ASCII bytes are non-printable, as in a non-UTF-8 locale.
+    pub fn utf8_names(mut self, utf8_names: bool) -> Self {
+        self.utf8_names = utf8_names;
+        self
+    }
+
+    /// Append a character to names which indicates the type of file, as per
+    /// `ls -F` (-F).
+    pub fn classify(mut self, classify: bool) -> Self {
+        self.classify = classify;
+        self
+    }
+
+    /// Print the size of each file (-s).
+    pub fn print_size(mut self, print_size: bool) -> Self {
+        self.print_size = print_size;
+        self
+    }
+
+    /// The units in which sizes are printed (-h, --si).
+    pub fn size_units(mut self, size_units: SizeUnits) -> Self {
+        self.size_units = size_units;
+        self
+    }
+
+    /// Print the size of directories as the total size of their contents, and
+    /// add the total size to the report (--du). Implies
+    /// [`print_size`](TreeOptionsBuilder::print_size).
+    ///
+    /// Since the sizes are needed before a directory is written, the tree must
+    /// be written with
+    /// [`Tree::write_disk_usage`](crate::tree::Tree::write_disk_usage), or
+    /// built with [`Tree::build`](crate::tree::Tree::build), rather than
+    /// written with [`Tree::write`](crate::tree::Tree::write).
+    pub fn disk_usage(mut self, disk_usage: bool) -> Self {
+        self.disk_usage = disk_usage;
+        self
+    }
+
+    /// The order in which the entries of each directory are listed (--sort,
+    /// -r).
+    pub fn sorter(mut self, sorter: Sorter) -> Self {
+        self.sorter = sorter;
+        self
+    }
+
+    /// List at most `max_entries` entries of each directory, followed by a
+    /// line showing how many more entries there are (--max-entries). Must be
+    /// greater than 0. Not available in compat mode.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Include the entries which are not listed because of
+    /// [`max_entries`](TreeOptionsBuilder::max_entries) in the report
+    /// (--count-elided).
+    pub fn count_elided_entries(mut self, count_elided_entries: bool) -> Self {
+        self.count_elided_entries = count_elided_entries;
+        self
+    }
+
+    /// Annotate directories which are not descended into because of
+    /// [`max_level`](TreeOptionsBuilder::max_level) with the number of
+    /// directories and files below them which would otherwise be listed
+    /// (--show-truncated). They are counted by walking the directories without
+    /// listing them. Not available in compat mode.
+    pub fn show_truncated(mut self, show_truncated: bool) -> Self {
+        self.show_truncated = show_truncated;
+        self
+    }
+
+    /// Include the entries counted by
+    /// [`show_truncated`](TreeOptionsBuilder::show_truncated) in the report
+    /// (--count-truncated).
+    pub fn count_truncated_entries(mut self, count_truncated_entries: bool) -> Self {
+        self.count_truncated_entries = count_truncated_entries;
+        self
+    }
+
+    /// List only files of one of the given types (--type). Not available in
+    /// compat mode.
+    pub fn file_types<I>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = FileTypeFilter>,
+    {
+        self.predicates.types = types.into_iter().collect();
+        self
+    }
+
+    /// List only regular files whose size is within all of the given limits
+    /// (--size). Not available in compat mode.
+    pub fn size_filters<I>(mut self, sizes: I) -> Self
+    where
+        I: IntoIterator<Item = SizeFilter>,
+    {
+        self.predicates.sizes = sizes.into_iter().collect();
+        self
+    }
+
+    /// List only files whose modification time is within all of the given
+    /// limits (--newer and --older). Not available in compat mode.
+    pub fn time_filters<I>(mut self, times: I) -> Self
+    where
+        I: IntoIterator<Item = TimeFilter>,
+    {
+        self.predicates.times = times.into_iter().collect();
+        self
+    }
+
+    /// Do not list directories which are empty once filtered, unless they
+    /// match the type, size and time filters themselves (--prune).
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Write directories whose only entry is a directory on the same line as
+    /// it, e.g. `com/example/project` (--collapse). Each collapsed directory
+    /// still counts as a level for
+    /// [`max_level`](TreeOptionsBuilder::max_level), and in the stats. Only
+    /// the text and HTML output are collapsed. Not available in compat mode.
+    pub fn collapse(mut self, collapse: bool) -> Self {
+        self.collapse = collapse;
+        self
+    }
+
+    /// Validates the options and compiles the patterns.
+    pub fn build(self) -> Result<TreeOptions> {
+        if self.max_level == Some(0) {
+            return Err(Error::InvalidOptions(
+                "Invalid level, must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.full_path != FullPath::AsGiven && self.compat {
+            return Err(Error::InvalidOptions(
+                "Absolute and relative paths are not supported in compat mode".to_string(),
+            ));
+        }
+
+        if self.max_entries == Some(0) {
+            return Err(Error::InvalidOptions(
+                "Invalid max entries, must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.max_entries.is_some() && self.compat {
+            return Err(Error::InvalidOptions(
+                "Limiting the entries of each directory is not supported in compat mode"
+                    .to_string(),
+            ));
+        }
+
+        if self.compat
+            && !(self.file_include_regexes.is_empty() && self.file_exclude_regexes.is_empty())
+        {
+            return Err(Error::InvalidOptions(
+                "Regex patterns are not supported in compat mode".to_string(),
+            ));
+        }
+
+        if self.compat && !self.predicates.is_empty() {
+            return Err(Error::InvalidOptions(
+                "Filtering by type, size or time is not supported in compat mode".to_string(),
+            ));
+        }
+
+        if self.compat && self.show_truncated {
+            return Err(Error::InvalidOptions(
+                "Showing the contents of truncated directories is not supported in compat mode"
+                    .to_string(),
+            ));
+        }
+
+        if self.compat && self.collapse {
+            return Err(Error::InvalidOptions(
+                "Collapsing directories is not supported in compat mode".to_string(),
+            ));
+        }
+
+        if self.match_dirs && !self.compat {
+            return Err(Error::InvalidOptions(
+                "Matching directory names requires compat mode".to_string(),
+            ));
+        }
+
+        let mut file_include_patterns = self.file_include_patterns;
+        for path in &self.file_include_pattern_files {
+            file_include_patterns.extend(read_pattern_file(path, self.compat)?);
+        }
+        let mut file_exclude_patterns = self.file_exclude_patterns;
+        for path in &self.file_exclude_pattern_files {
+            file_exclude_patterns.extend(read_pattern_file(path, self.compat)?);
+        }
+
+        Ok(TreeOptions {
+            compat: self.compat,
+            show_hidden_files: self.show_hidden_files,
+            list_directories_only: self.list_directories_only,
+            follow_links: self.follow_links,
+            print_full_path_prefix: self.print_full_path_prefix
+                || self.full_path != FullPath::AsGiven,
+            full_path: self.full_path,
+            max_level: self.max_level,
+            file_include_patterns: PatternSet::new(
+                file_include_patterns,
+                self.file_include_regexes,
+                self.ignore_case,
+                self.compat,
+            )?,
+            file_exclude_patterns: PatternSet::new(
+                file_exclude_patterns,
+                self.file_exclude_regexes,
+                self.ignore_case,
+                self.compat,
+            )?,
+            match_dirs: self.match_dirs,
+            respect_gitignore: self.respect_gitignore,
+            gitignore_files: self.gitignore_files,
+            icons: self.icons,
+            charset: self.charset,
+            colors: self.colors,
+            hyperlinks: self.hyperlinks,
+            non_printable: self.non_printable,
+            quote_names: self.quote_names,
+            utf8_names: self.utf8_names,
+            classify: self.classify,
+            print_size: self.print_size || self.disk_usage,
+            size_units: self.size_units,
+            disk_usage: self.disk_usage,
+            sorter: self.sorter,
+            max_entries: self.max_entries,
+            count_elided_entries: self.count_elided_entries,
+            show_truncated: self.show_truncated,
+            count_truncated_entries: self.show_truncated && self.count_truncated_entries,
+            predicates: self.predicates,
+            prune: self.prune,
+            collapse: self.collapse,
+        })
+    }
+}
+
+#[cfg(feature = "cli")]
 impl TryFrom<TreeArgs> for TreeOptions {
-    type Error = anyhow::Error;
-
-    fn try_from(args: TreeArgs) -> anyhow::Result<TreeOptions> {
-        Ok(Self {
-            compat: args.compat,
-            show_hidden_files: args.show_hidden_files,
-            list_directories_only: args.list_directories_only,
-            print_full_path_prefix: args.print_full_path_prefix,
-            max_level: args.max_level,
-            file_include_globset: build_globset(args.file_include_patterns, args.ignore_case)
-                .context("Failed to build matcher for file include patterns (-P)")?,
-            file_exclude_globset: build_globset(args.file_exclude_patterns, args.ignore_case)
-                .context("Failed to build matcher for file exclude patterns (-I)")?,
-            match_dirs: args.compat && args.match_dirs,
-            respect_gitignore: if args.compat {
-                args.gitignore
+    type Error = Error;
+
+    fn try_from(args: TreeArgs) -> Result<TreeOptions> {
+        let mut builder = TreeOptions::builder()
+            .compat(args.compat)
+            .show_hidden_files(args.show_hidden_files)
+            .list_directories_only(args.list_directories_only)
+            .follow_links(args.follow_links)
+            .print_full_path_prefix(args.print_full_path_prefix)
+            .full_path(match (args.full_path.as_deref(), args.relative_to) {
+                (_, Some(base)) => FullPath::RelativeTo(base.into()),
+                (Some("absolute"), None) => FullPath::Absolute,
+                (Some("relative"), None) => FullPath::RelativeToCurrentDir,
+                _ => FullPath::AsGiven,
+            })
+            .file_include_patterns(args.file_include_patterns)
+            .file_exclude_patterns(args.file_exclude_patterns)
+            .file_include_pattern_files(args.include_from)
+            .file_exclude_pattern_files(args.exclude_from)
+            .file_include_regexes(args.regex_include)
+            .file_exclude_regexes(args.regex_exclude)
+            .ignore_case(args.ignore_case)
+            .match_dirs(args.compat && args.match_dirs)
+            .respect_gitignore(if args.compat {
+                args.gitignore || !args.gitfile.is_empty()
             } else {
                 !args.no_gitignore
-            },
-            icons: if args.compat {
+            })
+            .gitignore_files(args.gitfile)
+            .icons(if args.compat {
                 args.icons
             } else {
                 !args.no_icons
-            },
-            sorter: default_sorter,
-        })
-    }
-}
+            })
+            .charset(match args.charset {
+                Some(charset) => Charset::from_name(&charset),
+                None if args.compat => Charset::from_env(),
+                None => Charset::Utf8,
+            })
+            .colors(if args.compat || args.color {
+                Colors::from_env(args.color, args.no_color)
+            } else {
+                None
+            })
+            .hyperlinks(Hyperlinks::from_mode(
+                args.hyperlink
+                    .as_deref()
+                    .and_then(HyperlinkMode::from_name)
+                    .unwrap_or_default(),
+                // The output is written to the -o file instead of stdout
+                args.output_to_file.is_none() && std::io::stdout().is_terminal(),
+            ))
+            .non_printable(if args.raw_non_printable {
+                NonPrintable::Raw
+            } else if args.replace_non_printable {
+                NonPrintable::Replace
+            } else {
+                NonPrintable::Escape
+            })
+            .quote_names(args.quote_names)
+            .classify(args.classify)
+            .print_size(args.print_size || args.human_readable || args.si_units)
+            .size_units(if args.si_units {
+                SizeUnits::Si
+            } else if args.human_readable {
+                SizeUnits::Binary
+            } else {
+                SizeUnits::Bytes
+            })
+            .disk_usage(args.disk_usage)
+            .sorter(
+                Sorter::by(
+                    args.sort
+                        .as_deref()
+                        .and_then(SortType::from_name)
+                        .unwrap_or_default(),
+                )
+                .reverse(args.reverse),
+            )
+            .utf8_names(!args.compat || locale_codeset_is_utf8())
+            .file_types(
+                args.file_type
+                    .iter()
+                    .filter_map(|ty| FileTypeFilter::from_name(ty)),
+            )
+            .size_filters(
+                args.size
+                    .iter()
+                    .map(|size| {
+                        SizeFilter::from_string(size)
+                            .ok_or_else(|| Error::InvalidOptions(format!("Invalid size ({size})")))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            )
+            .prune(args.prune)
+            .show_truncated(args.show_truncated)
+            .count_truncated_entries(args.count_truncated)
+            .collapse(args.collapse);
 
-/// Builds a GlobSet matcher from a collection of globs. Returns `Ok(None)` if
-/// the collection of globs is empty.
-fn build_globset(globs: Vec<String>, case_insensitive: bool) -> anyhow::Result<Option<GlobSet>> {
-    if globs.is_empty() {
-        return Ok(None);
-    }
+        let now = std::time::SystemTime::now();
+        let parse_time = |time: &String| {
+            crate::predicate::parse_time(time, now)
+                .ok_or_else(|| Error::InvalidOptions(format!("Invalid time ({time})")))
+        };
+        let mut times = Vec::new();
+        if let Some(newer) = &args.newer {
+            times.push(TimeFilter::NewerThan(parse_time(newer)?));
+        }
+        if let Some(older) = &args.older {
+            times.push(TimeFilter::OlderThan(parse_time(older)?));
+        }
+        builder = builder.time_filters(times);
 
-    // Split globs on | to match behaviour of reference implementation
-    let globs = globs.into_iter().flat_map(|s| {
-        s.split("|")
-            .map(|s| s.to_string())
-            .collect::<Box<[String]>>()
-    });
+        if let Some(max_level) = args.max_level {
+            builder = builder.max_level(max_level);
+        }
 
-    let mut file_include_globset_builder = GlobSet::builder();
+        if let Some(max_entries) = args.max_entries {
+            builder = builder
+                .max_entries(max_entries)
+                .count_elided_entries(args.count_elided);
+        }
 
-    for glob in globs {
-        // TODO: In compat mode we should port the glob parser from the
-        //       reference implementation.
-        // EXPLANATION: The glob parser used differes from the reference in a
-        //              number of ways, including accepting the {} syntax, and
-        //              how it handles invalid globs e.g. '*.[txt'
-        let glob = GlobBuilder::new(&glob)
-            .case_insensitive(case_insensitive)
-            .build()
-            .context("Failed to build glob")?;
-        file_include_globset_builder.add(glob);
+        builder.build()
     }
-
-    Ok(Some(file_include_globset_builder.build()?))
 }
@@ -0,0 +1,528 @@
+use std::{
+    ffi::OsStr,
+    os::unix::ffi::OsStrExt as _,
+    path::{Component, Path},
+};
+
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet};
+use regex::bytes::{RegexBuilder, RegexSet, RegexSetBuilder};
+
+use crate::error::{Error, Result};
+
+/// The patterns passed to -P or -I, and the regexes passed to --regex-include
+/// or --regex-exclude.
+pub(crate) enum PatternSet {
+    /// Patterns compiled with globset, which supports a richer syntax than the
+    /// reference implementation (e.g. `{a,b}`), and rejects invalid patterns.
+    Riptree {
+        globs: GlobPatterns,
+        regexes: RegexPatterns,
+    },
+    /// Patterns matched in the same way as the reference implementation (see
+    /// [`patmatch`]), used in compat mode.
+    Compat {
+        patterns: Vec<Vec<u8>>,
+        ignore_case: bool,
+    },
+}
+
+impl PatternSet {
+    /// Compiles the patterns and regexes. Returns `Ok(None)` if there are none.
+    /// Regexes are not supported in compat mode, and are ignored.
+    pub(crate) fn new(
+        patterns: Vec<String>,
+        regexes: Vec<String>,
+        ignore_case: bool,
+        compat: bool,
+    ) -> Result<Option<Self>> {
+        if patterns.is_empty() && regexes.is_empty() {
+            return Ok(None);
+        }
+
+        if compat {
+            return Ok(Some(Self::Compat {
+                patterns: patterns.into_iter().map(String::into_bytes).collect(),
+                ignore_case,
+            }));
+        }
+
+        Ok(Some(Self::Riptree {
+            globs: GlobPatterns::new(patterns, ignore_case)?,
+            regexes: RegexPatterns::new(regexes, ignore_case)?,
+        }))
+    }
+
+    /// Whether the entry with the given file name and path (relative to the
+    /// root) matches any of the patterns. The path is only used outside of
+    /// compat mode.
+    ///
+    /// Outside of compat mode, a pattern starting with `!` is negated: if it is
+    /// the last pattern to match an entry, the entry does not match, even if it
+    /// matches a regex.
+    #[inline]
+    pub(crate) fn is_match(&self, file_name: &OsStr, path: &Path, is_dir: bool) -> bool {
+        match self {
+            Self::Riptree { globs, regexes } => match globs.matched(file_name, path, is_dir) {
+                Some(matched) => matched,
+                None => regexes.is_match(file_name, path),
+            },
+            Self::Compat {
+                patterns,
+                ignore_case,
+            } => patterns
+                .iter()
+                .any(|pattern| patmatch(file_name.as_bytes(), pattern, is_dir, *ignore_case) == 1),
+        }
+    }
+
+    /// Whether the directory with the given name and path (relative to the
+    /// root) may contain entries which match a pattern containing a slash, e.g.
+    /// `src` for `src/**/*.rs`. A regex containing a slash may match below any
+    /// directory. Nothing matches below a directory which is matched by a
+    /// negated pattern, e.g. `src/gen` for `!src/gen/`.
+    #[inline]
+    pub(crate) fn may_match_below(&self, dir_name: &OsStr, dir: &Path) -> bool {
+        match self {
+            Self::Riptree { globs, regexes } => {
+                globs.matched(dir_name, dir, true) != Some(false)
+                    && (globs.may_match_below(dir) || !regexes.paths.is_empty())
+            }
+            Self::Compat { .. } => false,
+        }
+    }
+}
+
+/// Reads a file passed to --include-from or --exclude-from, which contains one
+/// pattern per line. Empty lines, and lines starting with `#`, are skipped. Like
+/// -P and -I, whitespace is part of the pattern, but a trailing `\r` is not.
+/// Outside of compat mode, each pattern is compiled, so that an invalid pattern
+/// can be reported along with its line number.
+pub(crate) fn read_pattern_file(path: &Path, compat: bool) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path).map_err(|source| Error::ReadPatternFile {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let mut patterns = Vec::new();
+    // str::lines also strips a trailing \r
+    for (index, pattern) in contents.lines().enumerate() {
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+
+        if !compat {
+            match GlobPatterns::new(vec![pattern.to_string()], false) {
+                Ok(_) => {}
+                Err(Error::InvalidPattern { source, .. }) => {
+                    return Err(Error::InvalidPatternFile {
+                        path: path.to_path_buf(),
+                        line: index + 1,
+                        pattern: pattern.to_string(),
+                        source,
+                    });
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        patterns.push(pattern.to_string());
+    }
+
+    Ok(patterns)
+}
+
+/// Patterns compiled with globset, with the same anchoring rules as a
+/// .gitignore file. Patterns which contain a slash (other than a trailing one)
+/// are matched against the path relative to the root, and other patterns are
+/// matched against the file name. A trailing slash only matches directories,
+/// and a leading `!` negates the pattern.
+pub(crate) struct GlobPatterns {
+    names: Globs,
+    paths: Globs,
+    /// The components of each path pattern which is not negated, where `None`
+    /// is a `**` component.
+    path_components: Vec<Vec<Option<GlobMatcher>>>,
+    /// Whether any of the patterns are negated, in which case the order of the
+    /// patterns matters.
+    negated: bool,
+}
+
+impl GlobPatterns {
+    fn new(patterns: Vec<String>, case_insensitive: bool) -> Result<Self> {
+        // Split globs on | to match behaviour of reference implementation. The
+        // alternatives share the position and negation of the pattern.
+        let patterns = patterns.into_iter().enumerate().flat_map(|(order, s)| {
+            let (s, negated) = match s.strip_prefix('!') {
+                Some(s) => (s, true),
+                None => (s.as_str(), false),
+            };
+            s.split("|")
+                .map(|s| (s.to_string(), order, negated))
+                .collect::<Box<[_]>>()
+        });
+
+        let compile = |glob: &str, pattern: &str| {
+            GlobBuilder::new(glob)
+                .case_insensitive(case_insensitive)
+                .literal_separator(true)
+                .build()
+                .map_err(|source| Error::InvalidPattern {
+                    pattern: pattern.to_string(),
+                    source,
+                })
+        };
+
+        let mut names = Vec::new();
+        let mut paths = Vec::new();
+        let mut path_components = Vec::new();
+        let mut any_negated = false;
+
+        for (pattern, order, negated) in patterns {
+            let (glob, dir_only) = match pattern.strip_suffix('/') {
+                Some(glob) => (glob, true),
+                None => (pattern.as_str(), false),
+            };
+            let flags = GlobFlags {
+                order,
+                dir_only,
+                negated,
+            };
+            any_negated |= negated;
+
+            if !glob.contains('/') {
+                names.push((compile(glob, &pattern)?, flags));
+                continue;
+            }
+
+            let glob = glob.strip_prefix('/').unwrap_or(glob);
+            paths.push((compile(glob, &pattern)?, flags));
+            if negated {
+                continue;
+            }
+            path_components.push(
+                glob.split('/')
+                    .map(|component| match component {
+                        "**" => Ok(None),
+                        _ => Ok(Some(compile(component, &pattern)?.compile_matcher())),
+                    })
+                    .collect::<Result<_>>()?,
+            );
+        }
+
+        Ok(Self {
+            names: Globs::new(names)?,
+            paths: Globs::new(paths)?,
+            path_components,
+            negated: any_negated,
+        })
+    }
+
+    /// Returns whether the last pattern to match the entry is not negated, or
+    /// `None` if no pattern matches it.
+    #[inline]
+    fn matched(&self, file_name: &OsStr, path: &Path, is_dir: bool) -> Option<bool> {
+        let file_name = Path::new(file_name);
+        if !self.negated {
+            let is_match =
+                self.names.is_match(file_name, is_dir) || self.paths.is_match(path, is_dir);
+            return is_match.then_some(true);
+        }
+
+        let last_match = match (
+            self.names.last_match(file_name, is_dir),
+            self.paths.last_match(path, is_dir),
+        ) {
+            (Some(name), Some(path)) => Some(if name.order > path.order { name } else { path }),
+            (name, path) => name.or(path),
+        };
+        last_match.map(|flags| !flags.negated)
+    }
+
+    fn may_match_below(&self, dir: &Path) -> bool {
+        self.path_components.iter().any(|components| {
+            let mut dir_components = dir.components().filter_map(|component| match component {
+                Component::Normal(name) => Some(name),
+                _ => None,
+            });
+            for component in components {
+                let Some(dir_component) = dir_components.next() else {
+                    // The directory is a prefix of the pattern
+                    return true;
+                };
+                match component {
+                    None => return true,
+                    Some(matcher) if !matcher.is_match(dir_component) => return false,
+                    Some(_) => {}
+                }
+            }
+            false
+        })
+    }
+}
+
+/// A set of globs, each of which may only match directories.
+struct Globs {
+    set: GlobSet,
+    flags: Vec<GlobFlags>,
+}
+
+#[derive(Clone, Copy)]
+struct GlobFlags {
+    /// The position of the pattern the glob was compiled from.
+    order: usize,
+    dir_only: bool,
+    negated: bool,
+}
+
+impl Globs {
+    fn new(globs: Vec<(Glob, GlobFlags)>) -> Result<Self> {
+        let mut builder = GlobSet::builder();
+        let mut flags = Vec::with_capacity(globs.len());
+        let mut patterns = Vec::with_capacity(globs.len());
+
+        for (glob, glob_flags) in globs {
+            patterns.push(glob.glob().to_string());
+            builder.add(glob);
+            flags.push(glob_flags);
+        }
+
+        let set = builder.build().map_err(|source| Error::InvalidPattern {
+            pattern: patterns.join("|"),
+            source,
+        })?;
+
+        Ok(Self { set, flags })
+    }
+
+    #[inline]
+    fn is_match(&self, candidate: &Path, is_dir: bool) -> bool {
+        if self.set.is_empty() {
+            return false;
+        }
+        if is_dir || !self.flags.iter().any(|flags| flags.dir_only) {
+            return self.set.is_match(candidate);
+        }
+
+        self.set
+            .matches(candidate)
+            .into_iter()
+            .any(|index| !self.flags[index].dir_only)
+    }
+
+    /// Returns the flags of the last glob (in the order of the patterns) which
+    /// matches the candidate.
+    #[inline]
+    fn last_match(&self, candidate: &Path, is_dir: bool) -> Option<GlobFlags> {
+        if self.set.is_empty() {
+            return None;
+        }
+
+        self.set
+            .matches(candidate)
+            .into_iter()
+            .map(|index| self.flags[index])
+            .filter(|flags| is_dir || !flags.dir_only)
+            .max_by_key(|flags| flags.order)
+    }
+}
+
+/// Regexes compiled into sets. Like globs, regexes which contain a slash are
+/// matched against the path relative to the root, and other regexes are
+/// matched against the file name.
+pub(crate) struct RegexPatterns {
+    names: RegexSet,
+    paths: RegexSet,
+}
+
+impl RegexPatterns {
+    fn new(regexes: Vec<String>, case_insensitive: bool) -> Result<Self> {
+        let (paths, names) = regexes
+            .into_iter()
+            .partition::<Vec<_>, _>(|regex| regex.contains('/'));
+
+        Ok(Self {
+            names: build_regex_set(names, case_insensitive)?,
+            paths: build_regex_set(paths, case_insensitive)?,
+        })
+    }
+
+    #[inline]
+    fn is_match(&self, file_name: &OsStr, path: &Path) -> bool {
+        self.names.is_match(file_name.as_bytes())
+            || self.paths.is_match(path.as_os_str().as_bytes())
+    }
+}
+
+fn build_regex_set(regexes: Vec<String>, case_insensitive: bool) -> Result<RegexSet> {
+    RegexSetBuilder::new(&regexes)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|source| {
+            // Report the regex which failed to compile, if there is one
+            regexes
+                .iter()
+                .find_map(|regex| {
+                    let source = RegexBuilder::new(regex).build().err()?;
+                    Some(Error::InvalidRegex {
+                        pattern: regex.clone(),
+                        source,
+                    })
+                })
+                .unwrap_or_else(|| Error::InvalidRegex {
+                    pattern: regexes.join("|"),
+                    source,
+                })
+        })
+}
+
+/// A port of `patmatch` from the reference implementation. Returns 1 on a
+/// match, 0 on a mismatch, and -1 if the pattern is invalid (e.g. an
+/// unterminated character class, or an empty `|` alternative).
+///
+/// Invalid patterns are not reported by the reference implementation. Since
+/// only a result of 1 is a match, they simply never match anything.
+pub(crate) fn patmatch(buf: &[u8], pat: &[u8], is_dir: bool, ignore_case: bool) -> i32 {
+    // If a bar is found, match each of the alternatives
+    if let Some(bar) = pat.iter().position(|&c| c == b'|') {
+        if bar == 0 || bar + 1 == pat.len() {
+            return -1;
+        }
+
+        let matched = patmatch(buf, &pat[..bar], is_dir, ignore_case);
+        if matched != 0 {
+            return matched;
+        }
+        return patmatch(buf, &pat[bar + 1..], is_dir, ignore_case);
+    }
+
+    // The C implementation relies on strings being NUL terminated, so reading
+    // past the end of either string yields 0.
+    let at = |s: &[u8], i: usize| s.get(i).copied().unwrap_or(0);
+    let rest = |b: usize, p: usize| {
+        patmatch(
+            buf.get(b..).unwrap_or_default(),
+            pat.get(p..).unwrap_or_default(),
+            is_dir,
+            ignore_case,
+        )
+    };
+
+    let mut matched = 1;
+    let (mut b, mut p) = (0, 0);
+
+    while at(pat, p) != 0 {
+        match at(pat, p) {
+            b'[' => {
+                p += 1;
+                let n = if at(pat, p) != b'^' {
+                    matched = 0;
+                    1
+                } else {
+                    p += 1;
+                    0
+                };
+                while at(pat, p) != b']' {
+                    if at(pat, p) == b'\\' {
+                        p += 1;
+                    }
+                    if at(pat, p) == 0 {
+                        return -1;
+                    }
+                    if at(pat, p + 1) == b'-' {
+                        let m = at(pat, p);
+                        p += 2;
+                        if at(pat, p) == b'\\' {
+                            p += 1;
+                        }
+                        if at(buf, b) >= m && at(buf, b) <= at(pat, p) {
+                            matched = n;
+                        }
+                        if at(pat, p) == 0 {
+                            p -= 1;
+                        }
+                    } else if at(buf, b) == at(pat, p) {
+                        matched = n;
+                    }
+                    p += 1;
+                }
+                b += 1;
+            }
+            b'*' => {
+                p += 1;
+                if at(pat, p) == 0 {
+                    return 1;
+                }
+
+                matched = 0;
+                if at(pat, p) == b'*' {
+                    // "Support" ** for .gitignore support, mostly the same as *
+                    p += 1;
+                    if at(pat, p) == 0 {
+                        return 1;
+                    }
+                    while at(buf, b) != 0 {
+                        matched = rest(b, p);
+                        if matched != 0 {
+                            break;
+                        }
+                        // ** between two /'s is allowed to match a null /
+                        if at(pat, p) == b'/' && at(pat, p + 1) != 0 {
+                            matched = rest(b, p + 1);
+                            if matched != 0 {
+                                break;
+                            }
+                        }
+                        b += 1;
+                        while at(buf, b) != 0 && at(buf, b) != b'/' {
+                            b += 1;
+                        }
+                    }
+                } else {
+                    while at(buf, b) != 0 {
+                        matched = rest(b, p);
+                        b += 1;
+                        if matched != 0 {
+                            break;
+                        }
+                    }
+                }
+                if matched == 0 && at(buf, b) == 0 {
+                    matched = rest(b, p);
+                }
+                return matched;
+            }
+            b'?' => {
+                if at(buf, b) == 0 {
+                    return 0;
+                }
+                b += 1;
+            }
+            b'/' => {
+                if at(pat, p + 1) == 0 && at(buf, b) == 0 {
+                    return is_dir as i32;
+                }
+                matched = (at(buf, b) == b'/') as i32;
+                b += 1;
+            }
+            c => {
+                let c = if c == b'\\' {
+                    p += 1;
+                    at(pat, p)
+                } else {
+                    c
+                };
+                matched = if ignore_case {
+                    at(buf, b).eq_ignore_ascii_case(&c)
+                } else {
+                    at(buf, b) == c
+                } as i32;
+                b += 1;
+            }
+        }
+        p += 1;
+        if matched < 1 {
+            return matched;
+        }
+    }
+
+    if at(buf, b) == 0 { matched } else { 0 }
+}
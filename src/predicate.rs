@@ -0,0 +1,277 @@
+use std::{
+    fs::Metadata,
+    os::unix::fs::MetadataExt as _,
+    time::{Duration, SystemTime},
+};
+
+use crate::entry::{Entry, FileKind};
+
+/// A type of file which is listed (--type).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileTypeFilter {
+    File,
+    Directory,
+    Symlink,
+    /// Executable regular files.
+    Executable,
+    Socket,
+    Pipe,
+    /// Empty regular files and directories.
+    Empty,
+}
+
+impl FileTypeFilter {
+    /// Returns the type with the given name, as accepted by --type, i.e. one of
+    /// `f`, `d`, `l`, `x`, `s`, `p` or `e`, or the equivalent full name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "f" | "file" => Some(Self::File),
+            "d" | "directory" => Some(Self::Directory),
+            "l" | "symlink" => Some(Self::Symlink),
+            "x" | "executable" => Some(Self::Executable),
+            "s" | "socket" => Some(Self::Socket),
+            "p" | "pipe" => Some(Self::Pipe),
+            "e" | "empty" => Some(Self::Empty),
+            _ => None,
+        }
+    }
+
+    fn matches(self, entry: &Entry) -> bool {
+        match self {
+            Self::File => entry.kind() == FileKind::File,
+            Self::Directory => entry.kind() == FileKind::Directory,
+            Self::Symlink => entry.kind() == FileKind::Symlink,
+            Self::Executable => entry
+                .target_metadata()
+                .is_some_and(|metadata| metadata.is_file() && metadata.mode() & 0o111 != 0),
+            Self::Socket => entry.kind() == FileKind::Socket,
+            Self::Pipe => entry.kind() == FileKind::Fifo,
+            Self::Empty => match entry.kind() {
+                FileKind::File => entry.metadata().is_some_and(|metadata| metadata.len() == 0),
+                FileKind::Directory => std::fs::read_dir(entry.path())
+                    .is_ok_and(|mut entries| entries.next().is_none()),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A limit on the size of the regular files which are listed (--size).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeFilter {
+    AtLeast(u64),
+    AtMost(u64),
+    Exactly(u64),
+}
+
+impl SizeFilter {
+    /// Parses a size limit as accepted by --size, i.e. a number of bytes
+    /// preceded by `+` (at least) or `-` (at most), and optionally followed by
+    /// a unit, e.g. `+10M` or `-1ki`. Units are case insensitive, and are
+    /// either decimal (`k`, `m`, `g`, `t`) or binary (`ki`, `mi`, `gi`, `ti`).
+    pub fn from_string(value: &str) -> Option<Self> {
+        let (limit, value): (fn(u64) -> Self, _) = match value.as_bytes().first()? {
+            b'+' => (Self::AtLeast, &value[1..]),
+            b'-' => (Self::AtMost, &value[1..]),
+            _ => (Self::Exactly, value),
+        };
+        let digits = value.bytes().take_while(u8::is_ascii_digit).count();
+        let (number, unit) = value.split_at(digits);
+
+        let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" => 1000,
+            "ki" | "kib" => 1 << 10,
+            "m" | "mb" => 1000 * 1000,
+            "mi" | "mib" => 1 << 20,
+            "g" | "gb" => 1000 * 1000 * 1000,
+            "gi" | "gib" => 1 << 30,
+            "t" | "tb" => 1000 * 1000 * 1000 * 1000,
+            "ti" | "tib" => 1 << 40,
+            _ => return None,
+        };
+
+        Some(limit(number.parse::<u64>().ok()?.checked_mul(multiplier)?))
+    }
+
+    fn matches(self, size: u64) -> bool {
+        match self {
+            Self::AtLeast(limit) => size >= limit,
+            Self::AtMost(limit) => size <= limit,
+            Self::Exactly(limit) => size == limit,
+        }
+    }
+}
+
+/// A limit on the modification time of the files which are listed (--newer
+/// and --older).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFilter {
+    NewerThan(SystemTime),
+    OlderThan(SystemTime),
+}
+
+impl TimeFilter {
+    fn matches(self, modified: SystemTime) -> bool {
+        match self {
+            Self::NewerThan(time) => modified > time,
+            Self::OlderThan(time) => modified < time,
+        }
+    }
+}
+
+/// Predicates on the type, size and modification time of entries, which must
+/// all be satisfied for an entry to be listed. Directories are listed
+/// regardless, so that their contents can be, unless they are pruned
+/// (--prune).
+#[derive(Debug, Clone, Default)]
+pub struct Predicates {
+    /// The entry must be one of these types, if there are any.
+    pub(crate) types: Vec<FileTypeFilter>,
+    pub(crate) sizes: Vec<SizeFilter>,
+    pub(crate) times: Vec<TimeFilter>,
+}
+
+impl Predicates {
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.sizes.is_empty() && self.times.is_empty()
+    }
+
+    /// Whether the entry satisfies all of the predicates. Only regular files
+    /// satisfy size limits.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if !self.types.is_empty() && !self.types.iter().any(|ty| ty.matches(entry)) {
+            return false;
+        }
+
+        if !self.sizes.is_empty() {
+            let Some(size) = entry
+                .metadata()
+                .filter(|metadata| metadata.is_file())
+                .map(Metadata::len)
+            else {
+                return false;
+            };
+            if !self.sizes.iter().all(|limit| limit.matches(size)) {
+                return false;
+            }
+        }
+
+        if !self.times.is_empty() {
+            let Some(modified) = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified().ok())
+            else {
+                return false;
+            };
+            if !self.times.iter().all(|limit| limit.matches(modified)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Parses the time accepted by --newer and --older, which is either a duration
+/// before `now` (e.g. `30min`, `2h`, `1w3d`), or a date in local time (e.g.
+/// `2024-01-31` or `2024-01-31 12:00:00`).
+pub fn parse_time(value: &str, now: SystemTime) -> Option<SystemTime> {
+    match parse_duration(value) {
+        Some(duration) => now.checked_sub(duration),
+        None => parse_date(value),
+    }
+}
+
+/// Parses a duration made up of numbers followed by units, e.g. `1h30min`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut rest = value;
+    let mut seconds = 0u64;
+
+    while !rest.is_empty() {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let letters = rest[digits..]
+            .bytes()
+            .take_while(u8::is_ascii_alphabetic)
+            .count();
+        let (number, unit) = (&rest[..digits], &rest[digits..digits + letters]);
+        rest = &rest[digits + letters..];
+
+        let multiplier = match unit {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            "d" | "day" | "days" => 24 * 60 * 60,
+            "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+            "y" | "year" | "years" => 365 * 24 * 60 * 60,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(number.parse::<u64>().ok()?.checked_mul(multiplier)?)?;
+    }
+
+    (!value.is_empty()).then(|| Duration::from_secs(seconds))
+}
+
+/// Parses a date, optionally followed by a time, in local time.
+fn parse_date(value: &str) -> Option<SystemTime> {
+    let (date, time) = match value.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let parse_fields = |value: &str, separator: char| {
+        value
+            .split(separator)
+            .map(|field| {
+                field
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|_| field.bytes().all(|byte| byte.is_ascii_digit()))
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+
+    let [year, month, day] = parse_fields(date, '-')?[..] else {
+        return None;
+    };
+    let (hour, minute, second) = match time.map(|time| parse_fields(time, ':')) {
+        None => (0, 0, 0),
+        Some(Some(fields)) => match fields[..] {
+            [hour, minute] => (hour, minute, 0),
+            [hour, minute, second] => (hour, minute, second),
+            _ => return None,
+        },
+        Some(None) => return None,
+    };
+
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&minute)
+        || !(0..=60).contains(&second)
+    {
+        return None;
+    }
+
+    // SAFETY: All fields of tm are integers (or a pointer, for tm_zone), for
+    // which zero is a valid value
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = year - 1900;
+    tm.tm_mon = month - 1;
+    tm.tm_mday = day;
+    tm.tm_hour = hour;
+    tm.tm_min = minute;
+    tm.tm_sec = second;
+    tm.tm_isdst = -1;
+
+    // SAFETY: tm is a valid, initialised struct
+    let timestamp = unsafe { libc::mktime(&mut tm) };
+    if timestamp == -1 {
+        return None;
+    }
+
+    match u64::try_from(timestamp) {
+        Ok(seconds) => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(seconds)),
+        Err(_) => SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(timestamp.unsigned_abs())),
+    }
+}
@@ -0,0 +1,451 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufWriter, Write},
+    os::unix::ffi::OsStrExt as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    charset::Charset,
+    error::{Error, Result},
+    filter::FilteredEntry,
+    hyperlink::percent_encode,
+    name::write_name,
+    options::TreeOptions,
+    render::{
+        annotation,
+        text::{child_prefix, write_indicator, write_info},
+        truncated_annotation,
+    },
+    stats::DefaultTreeStats,
+    tree::Tree,
+    visitor::TreeVisitor,
+};
+
+/// The name of the file written in each directory cut off by -L, when the
+/// tree is listed again from that directory (-R).
+pub static RERUN_FILE_NAME: &str = "00Tree.html";
+
+/// The version of the reference implementation whose HTML output is
+/// reproduced.
+static REFERENCE_VERSION: &str = "tree v2.1.1";
+
+/// Writes an HTML page listing the given roots, in the same format as the tree
+/// command (-H). Links are formed by appending the path of each entry,
+/// relative to its root, to `base_href`.
+///
+/// If `rerun` is true (-R), each directory which is cut off by -L is listed
+/// again in its own page, which is written to a file named
+/// [`RERUN_FILE_NAME`] in the directory, and linked to from this page. Like
+/// tree, a page which can't be written is skipped, and the error is passed to
+/// `on_page_error`.
+///
+/// Returns the stats of the listed entries, excluding those of any pages
+/// written for -R.
+pub fn write_html_page(
+    w: &mut impl Write,
+    roots: &[PathBuf],
+    options: Arc<TreeOptions>,
+    base_href: &str,
+    rerun: bool,
+    report: bool,
+    on_page_error: &mut impl FnMut(Error),
+) -> Result<DefaultTreeStats> {
+    write_intro(w, &options).map_err(Error::Write)?;
+
+    let mut stats = DefaultTreeStats::new(options.clone());
+    let mut cutoff_dirs = Vec::new();
+    for root in roots {
+        let tree = Tree::new(root.clone(), options.clone())?;
+        let writer = HtmlWriter::new(w, options.clone(), root, base_href).rerun(rerun);
+        let mut visitor = (writer, &mut stats);
+
+        // Directory sizes are only known once the tree has been built (--du)
+        if options.disk_usage {
+            let node = tree.build()?;
+            let size = node.entry().size();
+            visitor
+                .0
+                .write_root(Some(size), node.error().map(annotation))?;
+            node.visit(&mut visitor)?;
+            visitor.1.count_disk_usage(size);
+        } else {
+            let size = std::fs::metadata(root).ok().map(|metadata| metadata.len());
+            let annotation = std::fs::read_dir(root)
+                .is_err()
+                .then_some("error opening dir");
+            visitor.0.write_root(size, annotation)?;
+            tree.visit(&mut visitor)?;
+        }
+
+        cutoff_dirs.append(&mut visitor.0.cutoff_dirs);
+    }
+
+    write!(w, "\t</p>\n\t<p>\n\n").map_err(Error::Write)?;
+    if report {
+        stats.write(w)?;
+    }
+    write!(w, "\t<br><br>\n\t</p>\n").map_err(Error::Write)?;
+    write_outro(w).map_err(Error::Write)?;
+
+    for (dir, href) in cutoff_dirs {
+        let path = dir.join(RERUN_FILE_NAME);
+        if let Err(err) =
+            write_rerun_page(&path, dir, options.clone(), &href, report, on_page_error)
+        {
+            on_page_error(err);
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Writes the page listing a directory cut off by -L to `path` (-R).
+fn write_rerun_page(
+    path: &Path,
+    dir: PathBuf,
+    options: Arc<TreeOptions>,
+    base_href: &str,
+    report: bool,
+    on_page_error: &mut impl FnMut(Error),
+) -> Result<()> {
+    let write_file_error = |source| Error::WriteFile {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let file = File::create(path).map_err(write_file_error)?;
+    let mut writer = BufWriter::new(file);
+    write_html_page(
+        &mut writer,
+        &[dir],
+        options,
+        base_href,
+        true,
+        report,
+        on_page_error,
+    )
+    .map_err(|err| match err {
+        Error::Write(source) => write_file_error(source),
+        err => err,
+    })?;
+    writer.flush().map_err(write_file_error)
+}
+
+/// A visitor which writes the entries of a tree as lines of an HTML page, in
+/// the same format as the tree command (-H).
+pub struct HtmlWriter<'w, W: Write> {
+    w: &'w mut W,
+    options: Arc<TreeOptions>,
+    root: PathBuf,
+    base_href: String,
+    prefix: String,
+    /// The length of the indentation added for each directory which has been
+    /// entered, which is 0 for directories collapsed into the line of their
+    /// only entry.
+    prefix_lens: Vec<usize>,
+    /// Whether the last line written is for the last entry of its directory.
+    is_last: bool,
+    /// The names of the directories which are collapsed into the next line
+    /// (--collapse), and whether the first of them is the last entry of its
+    /// directory, and its size.
+    collapsed: Option<(PathBuf, bool, u64)>,
+    annotation: Option<Cow<'static, str>>,
+    rerun: bool,
+    /// Directories which are cut off by -L, and the links to them (-R).
+    cutoff_dirs: Vec<(PathBuf, String)>,
+}
+
+impl<'w, W: Write> HtmlWriter<'w, W> {
+    pub fn new(w: &'w mut W, options: Arc<TreeOptions>, root: &Path, base_href: &str) -> Self {
+        Self {
+            w,
+            options,
+            root: root.to_path_buf(),
+            base_href: base_href.to_string(),
+            prefix: String::new(),
+            prefix_lens: Vec::new(),
+            is_last: false,
+            collapsed: None,
+            annotation: None,
+            rerun: false,
+            cutoff_dirs: Vec::new(),
+        }
+    }
+
+    /// Link directories which are cut off by -L to the pages listing them,
+    /// which are written by [`write_html_page`] (-R).
+    pub fn rerun(mut self, rerun: bool) -> Self {
+        self.rerun = rerun;
+        self
+    }
+
+    /// Writes the line naming the root of the tree, which links to the base
+    /// href.
+    pub fn write_root(&mut self, size: Option<u64>, annotation: Option<&str>) -> Result<()> {
+        self.write_root_line(size, annotation).map_err(Error::Write)
+    }
+
+    fn write_root_line(
+        &mut self,
+        size: Option<u64>,
+        annotation: Option<&str>,
+    ) -> std::io::Result<()> {
+        write!(self.w, "\t")?;
+        if let Some(size) = size {
+            write_info(self.w, &self.options, size)?;
+        }
+        write!(self.w, "<a href=\"{}\">", escape(self.base_href.as_bytes()))?;
+        write_html_name(self.w, &self.options, self.root.as_os_str().as_bytes())?;
+        write!(self.w, "</a>")?;
+        write_indicator(
+            self.w,
+            &self.options,
+            std::fs::metadata(&self.root).ok().as_ref(),
+        )?;
+        self.write_annotation(annotation)?;
+        writeln!(self.w, "<br>")?;
+        Ok(())
+    }
+
+    /// Returns the link to the given path below the root.
+    #[inline]
+    fn href(&self, path: &Path) -> String {
+        let mut href = self.base_href.trim_end_matches('/').to_string();
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        href.push('/');
+        percent_encode(&mut href, relative.as_os_str().as_bytes());
+        href
+    }
+
+    /// Writes the line for a single entry of the tree.
+    fn write_entry(
+        &mut self,
+        entry: &FilteredEntry,
+        link_target: Option<&Path>,
+        depth: usize,
+        is_last: bool,
+    ) -> std::io::Result<()> {
+        let entry = entry.as_ref();
+        // See TextWriter::entry
+        let (name, is_last, size) = match self.collapsed.take() {
+            Some((mut path, is_last, size)) => {
+                path.push(entry.file_name());
+                (path.into_os_string(), is_last, size)
+            }
+            None => (entry.file_name().to_os_string(), is_last, entry.size()),
+        };
+        self.is_last = is_last;
+        let branch = if is_last {
+            self.options.charset.corner()
+        } else {
+            self.options.charset.branch()
+        };
+        write!(self.w, "\t{}{branch}", self.prefix)?;
+        write_info(self.w, &self.options, size)?;
+
+        let is_dir = entry.target_is_dir();
+        let mut href = self.href(entry.path());
+        if is_dir {
+            href.push('/');
+        }
+        let is_cutoff = self.rerun
+            && is_dir
+            && self.options.max_level == Some(depth)
+            && (!entry.file_type().is_symlink() || self.options.follow_links);
+        if is_cutoff {
+            self.cutoff_dirs.push((
+                entry.path().to_path_buf(),
+                href.trim_end_matches('/').to_string(),
+            ));
+            href.push_str(RERUN_FILE_NAME);
+        }
+
+        write!(self.w, "<a href=\"{}\">", escape(href.as_bytes()))?;
+        write_html_name(self.w, &self.options, name.as_bytes())?;
+        write!(self.w, "</a>")?;
+        if let Some(link_target) = link_target {
+            write!(self.w, " -> ")?;
+            write_html_name(self.w, &self.options, link_target.as_os_str().as_bytes())?;
+        }
+        let metadata = if link_target.is_some() {
+            entry.target_metadata()
+        } else {
+            entry.metadata()
+        };
+        write_indicator(self.w, &self.options, metadata)?;
+        let annotation = self.annotation.take();
+        self.write_annotation(annotation.as_deref())?;
+        writeln!(self.w, "<br>")?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn write_annotation(&mut self, annotation: Option<&str>) -> std::io::Result<()> {
+        if let Some(annotation) = annotation {
+            write!(self.w, "  [{annotation}]")?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> TreeVisitor for HtmlWriter<'_, W> {
+    fn entry(
+        &mut self,
+        entry: &FilteredEntry,
+        link_target: Option<&Path>,
+        depth: usize,
+        is_last: bool,
+    ) -> Result<()> {
+        self.write_entry(entry, link_target, depth, is_last)
+            .map_err(Error::Write)
+    }
+
+    fn collapsed_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        if !self.options.collapse {
+            self.entry(dir, None, depth, is_last)?;
+            return self.enter_dir(dir, depth, is_last);
+        }
+
+        match &mut self.collapsed {
+            Some((path, ..)) => path.push(dir.as_ref().file_name()),
+            None => {
+                self.collapsed = Some((
+                    dir.as_ref().file_name().into(),
+                    is_last,
+                    dir.as_ref().size(),
+                ))
+            }
+        }
+        self.prefix_lens.push(0);
+        Ok(())
+    }
+
+    fn enter_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        // See TextWriter::enter_dir
+        let prefix = html_prefix(child_prefix(&self.options, self.is_last));
+        self.prefix.push_str(&prefix);
+        self.prefix_lens.push(prefix.len());
+        Ok(())
+    }
+
+    fn leave_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        let len = self.prefix.len() - self.prefix_lens.pop().unwrap_or_default();
+        self.prefix.truncate(len);
+        Ok(())
+    }
+
+    fn truncated(
+        &mut self,
+        _dir: &FilteredEntry,
+        dirs: usize,
+        files: usize,
+        _depth: usize,
+    ) -> Result<()> {
+        self.annotation = Some(truncated_annotation(dirs, files));
+        Ok(())
+    }
+
+    fn error(&mut self, _path: &Path, error: &Error, depth: usize) -> Result<()> {
+        // The root line is written separately, see HtmlWriter::write_root.
+        if depth > 0 {
+            self.annotation = Some(Cow::Borrowed(annotation(error)));
+        }
+        Ok(())
+    }
+}
+
+/// Writes a name, escaped for HTML.
+#[inline]
+fn write_html_name(w: &mut impl Write, options: &TreeOptions, name: &[u8]) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    write_name(&mut buf, name, options.name_style())?;
+    write!(w, "{}", escape(&buf))?;
+    Ok(())
+}
+
+/// Replaces the spaces in the indentation of a tree with non-breaking spaces,
+/// so that browsers don't collapse them.
+#[inline]
+fn html_prefix(prefix: &str) -> String {
+    prefix.replace(['\u{a0}', ' '], "&nbsp;")
+}
+
+/// Escapes the characters of a name or link which are special in HTML.
+fn escape(value: &[u8]) -> String {
+    let value = String::from_utf8_lossy(value);
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn write_intro(w: &mut impl Write, options: &TreeOptions) -> std::io::Result<()> {
+    let charset = match options.charset {
+        Charset::Utf8 => "UTF-8",
+        Charset::Ascii => "iso-8859-1",
+    };
+    let title = "Directory Tree";
+
+    write!(
+        w,
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n \
+         <meta http-equiv=\"Content-Type\" content=\"text/html; charset={charset}\">\n \
+         <meta name=\"Author\" content=\"Made by 'tree'\">\n \
+         <meta name=\"GENERATOR\" content=\"{REFERENCE_VERSION} &copy; 1996 - 2023 by Steve Baker, Thomas Moore, Francesc Rocher, Florian Sesser, Kyosuke Tokoro\">\n \
+         <title>{title}</title>\n \
+         <style type=\"text/css\">\n  \
+         BODY {{ font-family : monospace, sans-serif;  color: black;}}\n  \
+         P {{ font-family : monospace, sans-serif; color: black; margin:0px; padding: 0px;}}\n  \
+         A:visited {{ text-decoration : none; margin : 0px; padding : 0px;}}\n  \
+         A:link    {{ text-decoration : none; margin : 0px; padding : 0px;}}\n  \
+         A:hover   {{ text-decoration: underline; background-color : yellow; margin : 0px; padding : 0px;}}\n  \
+         A:active  {{ margin : 0px; padding : 0px;}}\n  \
+         .VERSION {{ font-size: small; font-family : arial, sans-serif; }}\n  \
+         .NORM  {{ color: black;  }}\n  \
+         .FIFO  {{ color: purple; }}\n  \
+         .CHAR  {{ color: yellow; }}\n  \
+         .DIR   {{ color: blue;   }}\n  \
+         .BLOCK {{ color: yellow; }}\n  \
+         .LINK  {{ color: aqua;   }}\n  \
+         .SOCK  {{ color: fuchsia;}}\n  \
+         .EXEC  {{ color: green;  }}\n \
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         \t<h1>{title}</h1><p>\n"
+    )?;
+
+    Ok(())
+}
+
+fn write_outro(w: &mut impl Write) -> std::io::Result<()> {
+    write!(
+        w,
+        "\t<hr>\n\
+         \t<p class=\"VERSION\">\n\
+         \t\t {REFERENCE_VERSION} &copy; 1996 - 2023 by Steve Baker and Thomas Moore <br>\n\
+         \t\t HTML output hacked and copyleft &copy; 1998 by Francesc Rocher <br>\n\
+         \t\t JSON output hacked and copyleft &copy; 2014 by Florian Sesser <br>\n\
+         \t\t Charsets / OS/2 support &copy; 2001 by Kyosuke Tokoro\n\
+         \t</p>\n\
+         </body>\n\
+         </html>\n"
+    )?;
+
+    Ok(())
+}
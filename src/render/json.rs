@@ -0,0 +1,185 @@
+use std::{io::Write, sync::Arc};
+
+use crate::{
+    entry::FileKind,
+    error::{Error, Result},
+    full_path::PathDisplay,
+    model::Node,
+    options::TreeOptions,
+    render::annotation,
+    stats::DefaultTreeStats,
+};
+
+/// Renders in-memory trees in the same JSON format as the tree command (-J).
+pub struct JsonRenderer {
+    options: Arc<TreeOptions>,
+}
+
+impl JsonRenderer {
+    pub fn new(options: Arc<TreeOptions>) -> Self {
+        Self { options }
+    }
+
+    /// Renders a JSON array containing each of the roots, followed by the
+    /// report if `report` is true.
+    pub fn render(&self, w: &mut impl Write, roots: &[Node], report: bool) -> Result<()> {
+        writeln!(w, "[").map_err(Error::Write)?;
+
+        for (index, root) in roots.iter().enumerate() {
+            if index > 0 {
+                writeln!(w, ",").map_err(Error::Write)?;
+            }
+            let paths = PathDisplay::new(&self.options.full_path, root.path())?;
+            self.render_node(
+                w,
+                root,
+                &paths,
+                &paths.display_root().to_string_lossy(),
+                1,
+                false,
+            )
+            .map_err(Error::Write)?;
+        }
+
+        if report {
+            let mut stats = DefaultTreeStats::new(self.options.clone());
+            for root in roots {
+                root.visit(&mut stats)?;
+                stats.count_disk_usage(root.entry().size());
+            }
+            self.render_report(w, &stats).map_err(Error::Write)?;
+        }
+
+        writeln!(w, "]").map_err(Error::Write)?;
+
+        Ok(())
+    }
+
+    fn render_report(&self, w: &mut impl Write, stats: &DefaultTreeStats) -> std::io::Result<()> {
+        writeln!(w, ",")?;
+        write!(w, "  {{\"type\":\"report\"")?;
+        if self.options.disk_usage {
+            write!(w, ",\"size\":{}", stats.disk_usage())?;
+        }
+        if self.options.list_directories_only {
+            writeln!(w, ",\"directories\":{}}}", stats.dirs())?;
+        } else {
+            writeln!(
+                w,
+                ",\"directories\":{},\"files\":{}}}",
+                stats.dirs(),
+                stats.files()
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn render_node(
+        &self,
+        w: &mut impl Write,
+        node: &Node,
+        paths: &PathDisplay,
+        name: &str,
+        level: usize,
+        needs_comma: bool,
+    ) -> std::io::Result<()> {
+        let indent = "  ".repeat(level);
+        let comma = if needs_comma { "," } else { "" };
+
+        write!(
+            w,
+            "{indent}{{\"type\":\"{}\",\"name\":\"{}\"",
+            json_type(node.kind()),
+            escape(name)
+        )?;
+
+        if let Some(link_target) = node.link_target() {
+            write!(
+                w,
+                ",\"target\":\"{}\"",
+                escape(&link_target.to_string_lossy())
+            )?;
+        }
+
+        if self.options.print_size {
+            write!(w, ",\"size\":{}", node.entry().size())?;
+        }
+
+        if let Some((dirs, files)) = node.truncated() {
+            write!(
+                w,
+                ",\"truncated\":{{\"directories\":{dirs},\"files\":{files}}}"
+            )?;
+        }
+
+        if let Some(error) = node.error() {
+            writeln!(
+                w,
+                ",\"contents\":[{{\"error\": \"{}\"}}]}}{comma}",
+                annotation(error)
+            )?;
+            return Ok(());
+        }
+
+        let Some(children) = node.children() else {
+            writeln!(w, "}}{comma}")?;
+            return Ok(());
+        };
+
+        writeln!(w, ",\"contents\":[")?;
+        for (index, child) in children.iter().enumerate() {
+            let name = if self.options.print_full_path_prefix {
+                paths.path(child.path()).to_string_lossy().into_owned()
+            } else {
+                child.name().to_string_lossy().into_owned()
+            };
+            let needs_comma = index + 1 < children.len() || !node.elided().is_empty();
+            self.render_node(w, child, paths, &name, level + 1, needs_comma)?;
+        }
+        // Entries which are not listed because of --max-entries
+        if !node.elided().is_empty() {
+            writeln!(
+                w,
+                "{indent}  {{\"type\":\"elided\",\"count\":{}}}",
+                node.elided().len()
+            )?;
+        }
+        writeln!(w, "{indent}]}}{comma}")?;
+
+        Ok(())
+    }
+}
+
+fn json_type(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::File | FileKind::Unknown => "file",
+        FileKind::Directory => "directory",
+        FileKind::Symlink => "link",
+        FileKind::Fifo => "fifo",
+        FileKind::Socket => "socket",
+        FileKind::BlockDevice => "block",
+        FileKind::CharDevice => "char",
+    }
+}
+
+/// Escapes a string for use in a JSON string literal.
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,27 @@
+//! Renderers which turn an in-memory tree (see [`Node`](crate::model::Node))
+//! into text, and writers which do so as a tree is traversed.
+
+pub mod html;
+pub mod json;
+pub mod text;
+
+use std::borrow::Cow;
+
+use crate::error::Error;
+
+/// Returns the annotation used by the tree command for a directory which
+/// couldn't be listed.
+pub(crate) fn annotation(error: &Error) -> &'static str {
+    match error {
+        Error::RecursiveLink { .. } => "recursive, not followed",
+        _ => "error opening dir",
+    }
+}
+
+/// Returns the annotation for a directory which is not descended into because
+/// of -L, e.g. "2 dirs, 5 files" (--show-truncated).
+pub(crate) fn truncated_annotation(dirs: usize, files: usize) -> Cow<'static, str> {
+    let dirs_noun = if dirs == 1 { "dir" } else { "dirs" };
+    let files_noun = if files == 1 { "file" } else { "files" };
+    Cow::Owned(format!("{dirs} {dirs_noun}, {files} {files_noun}"))
+}
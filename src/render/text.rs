@@ -0,0 +1,759 @@
+use std::{
+    borrow::Cow,
+    ffi::{OsStr, OsString},
+    fs::Metadata,
+    io::Write,
+    os::unix::{ffi::OsStrExt as _, fs::MetadataExt as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    charset::Charset,
+    entry::{Entry, FileKind},
+    error::{Error, Result},
+    filter::FilteredEntry,
+    full_path::PathDisplay,
+    icons::*,
+    model::Node,
+    name::write_name,
+    options::TreeOptions,
+    render::{annotation, truncated_annotation},
+    size::format_size,
+    stats::DefaultTreeStats,
+    visitor::TreeVisitor,
+};
+
+/// Renders an in-memory tree in the same text format as the tree command.
+pub struct TextRenderer {
+    options: Arc<TreeOptions>,
+}
+
+impl TextRenderer {
+    pub fn new(options: Arc<TreeOptions>) -> Self {
+        Self { options }
+    }
+
+    /// Renders the root node and all of its descendants.
+    pub fn render(&self, w: &mut impl Write, root: &Node) -> Result<()> {
+        let paths = PathDisplay::new(&self.options.full_path, root.path())?;
+        write_root_line(
+            w,
+            &self.options,
+            root.path(),
+            paths.display_root(),
+            Some(root.entry().size()),
+            root.error().map(annotation),
+        )
+        .map_err(Error::Write)?;
+        root.visit(&mut TextWriter::new(w, self.options.clone()).with_paths(paths))
+    }
+
+    /// Renders the report for the given root nodes, e.g. "3 directories, 6
+    /// files".
+    pub fn render_report(&self, w: &mut impl Write, roots: &[Node]) -> Result<()> {
+        let mut stats = DefaultTreeStats::new(self.options.clone());
+        for root in roots {
+            root.visit(&mut stats)?;
+            stats.count_disk_usage(root.entry().size());
+        }
+        writeln!(w).map_err(Error::Write)?;
+        stats.write(w)
+    }
+}
+
+/// A visitor which writes the entries of a tree in the same text format as the
+/// tree command, as they are visited.
+pub struct TextWriter<'w, W: Write> {
+    w: &'w mut W,
+    options: Arc<TreeOptions>,
+    prefix: String,
+    /// The length of the indentation added for each directory which has been
+    /// entered, which is 0 for directories collapsed into the line of their
+    /// only entry.
+    prefix_lens: Vec<usize>,
+    /// Whether the last line written is for the last entry of its directory.
+    is_last: bool,
+    /// The names of the directories which are collapsed into the next line
+    /// (--collapse), and whether the first of them is the last entry of its
+    /// directory, and its size.
+    collapsed: Option<(PathBuf, bool, u64)>,
+    annotation: Option<Cow<'static, str>>,
+    paths: Option<PathDisplay>,
+}
+
+impl<'w, W: Write> TextWriter<'w, W> {
+    pub fn new(w: &'w mut W, options: Arc<TreeOptions>) -> Self {
+        Self {
+            w,
+            options,
+            prefix: String::new(),
+            prefix_lens: Vec::new(),
+            is_last: false,
+            collapsed: None,
+            annotation: None,
+            paths: None,
+        }
+    }
+
+    /// Maps the paths of the entries to the paths which are printed (-f).
+    pub(crate) fn with_paths(mut self, paths: PathDisplay) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    fn name<'e>(&self, entry: &'e Entry) -> Cow<'e, OsStr> {
+        entry_name(&self.options, self.paths.as_ref(), entry)
+    }
+}
+
+impl<W: Write> TreeVisitor for TextWriter<'_, W> {
+    fn entry(
+        &mut self,
+        entry: &FilteredEntry,
+        link_target: Option<&Path>,
+        _depth: usize,
+        is_last: bool,
+    ) -> Result<()> {
+        let entry = entry.as_ref();
+        // A collapsed line shows the size of the first directory, by which it
+        // is sorted
+        let (name, is_last, size) = match self.collapsed.take() {
+            Some((mut path, is_last, size)) => {
+                path.push(entry.file_name());
+                (Cow::Owned(path.into_os_string()), is_last, size)
+            }
+            None => (self.name(entry), is_last, entry.size()),
+        };
+        self.is_last = is_last;
+        write_entry_line(
+            self.w,
+            &self.options,
+            &self.prefix,
+            entry,
+            size,
+            is_last,
+            &name,
+            link_target,
+            self.annotation.take().as_deref(),
+        )
+        .map_err(Error::Write)
+    }
+
+    fn collapsed_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        if !self.options.collapse {
+            self.entry(dir, None, depth, is_last)?;
+            return self.enter_dir(dir, depth, is_last);
+        }
+
+        match &mut self.collapsed {
+            Some((path, ..)) => path.push(dir.as_ref().file_name()),
+            None => {
+                let name = self.name(dir.as_ref()).into_owned();
+                self.collapsed = Some((name.into(), is_last, dir.as_ref().size()));
+            }
+        }
+        self.prefix_lens.push(0);
+        Ok(())
+    }
+
+    fn enter_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        // The last line written is for the directory, and the directories
+        // collapsed into it (if any), which share its branch
+        let prefix = child_prefix(&self.options, self.is_last);
+        self.prefix.push_str(prefix);
+        self.prefix_lens.push(prefix.len());
+        Ok(())
+    }
+
+    fn leave_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        let len = self.prefix.len() - self.prefix_lens.pop().unwrap_or_default();
+        self.prefix.truncate(len);
+        Ok(())
+    }
+
+    fn elided(&mut self, entries: &[&FilteredEntry], _depth: usize) -> Result<()> {
+        write_elided_line(self.w, &self.options, &self.prefix, entries.len()).map_err(Error::Write)
+    }
+
+    fn truncated(
+        &mut self,
+        _dir: &FilteredEntry,
+        dirs: usize,
+        files: usize,
+        _depth: usize,
+    ) -> Result<()> {
+        self.annotation = Some(truncated_annotation(dirs, files));
+        Ok(())
+    }
+
+    fn error(&mut self, _path: &Path, error: &Error, depth: usize) -> Result<()> {
+        // The root line is written separately, see write_root_line.
+        if depth > 0 {
+            self.annotation = Some(Cow::Borrowed(annotation(error)));
+        }
+        Ok(())
+    }
+}
+
+/// A visitor which writes the entries of a tree in the same text format as
+/// [`TextWriter`], with the size of each directory computed from its contents
+/// (--du).
+///
+/// The size of a directory is only known once it has been left, and the
+/// entries of a directory are then sorted again by their sizes, so the lines
+/// of each directory are buffered until it is left. The root line is written
+/// first, so nothing is written before [`DiskUsageWriter::finish`].
+pub(crate) struct DiskUsageWriter {
+    options: Arc<TreeOptions>,
+    paths: PathDisplay,
+    /// The root, followed by each directory which has been entered.
+    dirs: Vec<PendingDir>,
+    annotation: Option<Cow<'static, str>>,
+    root_annotation: Option<&'static str>,
+}
+
+/// An entry whose line has not been written yet, and the lines below it.
+struct PendingEntry {
+    entry: Entry,
+    name: OsString,
+    link_target: Option<PathBuf>,
+    annotation: Option<Cow<'static, str>>,
+    /// The directory collapsed into the line of the entry (--collapse), which
+    /// is sorted in its place.
+    collapsed_dir: Option<Entry>,
+    /// The lines below the entry, without the indentation of the entry.
+    lines: Vec<Vec<u8>>,
+}
+
+impl PendingEntry {
+    /// The entry which is sorted among the entries of its directory, and whose
+    /// size is added to the size of the directory.
+    fn sorted_entry(&self) -> &Entry {
+        self.collapsed_dir.as_ref().unwrap_or(&self.entry)
+    }
+}
+
+/// A directory whose entries are being visited.
+struct PendingDir {
+    /// The directory, which is `None` for the root.
+    dir: Option<PendingEntry>,
+    /// Whether the directory is collapsed into the line of its only entry.
+    collapsed: bool,
+    entries: Vec<PendingEntry>,
+    elided: usize,
+    elided_size: u64,
+}
+
+impl PendingDir {
+    fn new(dir: Option<PendingEntry>, collapsed: bool) -> Self {
+        Self {
+            dir,
+            collapsed,
+            entries: Vec::new(),
+            elided: 0,
+            elided_size: 0,
+        }
+    }
+}
+
+impl DiskUsageWriter {
+    pub(crate) fn new(options: Arc<TreeOptions>, paths: PathDisplay) -> Self {
+        Self {
+            options,
+            paths,
+            dirs: vec![PendingDir::new(None, false)],
+            annotation: None,
+            root_annotation: None,
+        }
+    }
+
+    /// Writes the root line, followed by the buffered lines of its entries,
+    /// and returns the size of the root.
+    pub(crate) fn finish(mut self, w: &mut impl Write, root: &Path) -> Result<u64> {
+        let Some(dir) = self.dirs.pop() else {
+            return Ok(0);
+        };
+        let (lines, contents_size) = self.close_dir(dir);
+        let size = std::fs::symlink_metadata(root)
+            .ok()
+            .map(|metadata| metadata.len() + contents_size);
+
+        write_root_line(
+            w,
+            &self.options,
+            root,
+            self.paths.display_root(),
+            size,
+            self.root_annotation,
+        )
+        .and_then(|()| lines.iter().try_for_each(|line| w.write_all(line)))
+        .map_err(Error::Write)?;
+
+        Ok(size.unwrap_or_default())
+    }
+
+    /// Returns the entry for the line of `entry`, with the annotation set
+    /// before it was visited.
+    fn pending_entry(&mut self, entry: &Entry, link_target: Option<&Path>) -> PendingEntry {
+        // The names of the directories collapsed into the line are prepended
+        let collapsed_dir = self
+            .dirs
+            .last()
+            .filter(|dir| dir.collapsed)
+            .and_then(|dir| dir.dir.as_ref());
+        let name = match collapsed_dir {
+            Some(dir) => {
+                let mut path = PathBuf::from(&dir.name);
+                path.push(entry.file_name());
+                path.into_os_string()
+            }
+            None => entry_name(&self.options, Some(&self.paths), entry).into_owned(),
+        };
+
+        PendingEntry {
+            entry: entry.clone(),
+            name,
+            link_target: link_target.map(Path::to_path_buf),
+            annotation: self.annotation.take(),
+            collapsed_dir: None,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Sorts the entries of the directory again, now that their sizes are
+    /// known, and returns their lines and the sum of their sizes.
+    fn close_dir(&self, mut dir: PendingDir) -> (Vec<Vec<u8>>, u64) {
+        dir.entries.sort_by(|a, b| {
+            self.options
+                .sorter
+                .compare(a.sorted_entry(), b.sorted_entry())
+        });
+
+        let mut lines = Vec::new();
+        let mut size = dir.elided_size;
+        let len = dir.entries.len();
+        for (index, entry) in dir.entries.into_iter().enumerate() {
+            let is_last = index + 1 == len && dir.elided == 0;
+            size += entry.sorted_entry().size();
+
+            let mut line = branch(&self.options, is_last).as_bytes().to_vec();
+            // Writing to a Vec can't fail
+            let _ = write_entry(
+                &mut line,
+                &self.options,
+                &entry.entry,
+                entry.sorted_entry().size(),
+                &entry.name,
+                entry.link_target.as_deref(),
+                entry.annotation.as_deref(),
+            );
+            lines.push(line);
+
+            let prefix = child_prefix(&self.options, is_last).as_bytes();
+            lines.extend(entry.lines.into_iter().map(|line| [prefix, &line].concat()));
+        }
+
+        if dir.elided > 0 {
+            let mut line = Vec::new();
+            let _ = write_elided_line(&mut line, &self.options, "", dir.elided);
+            lines.push(line);
+        }
+
+        (lines, size)
+    }
+}
+
+impl TreeVisitor for DiskUsageWriter {
+    fn entry(
+        &mut self,
+        entry: &FilteredEntry,
+        link_target: Option<&Path>,
+        _depth: usize,
+        _is_last: bool,
+    ) -> Result<()> {
+        let entry = self.pending_entry(entry.as_ref(), link_target);
+        if let Some(dir) = self.dirs.last_mut() {
+            dir.entries.push(entry);
+        }
+        Ok(())
+    }
+
+    fn collapsed_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        if !self.options.collapse {
+            self.entry(dir, None, depth, is_last)?;
+            return self.enter_dir(dir, depth, is_last);
+        }
+
+        let dir = self.pending_entry(dir.as_ref(), None);
+        self.dirs.push(PendingDir::new(Some(dir), true));
+        Ok(())
+    }
+
+    fn enter_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        // The directory was passed to entry just before
+        let dir = self.dirs.last_mut().and_then(|dir| dir.entries.pop());
+        self.dirs.push(PendingDir::new(dir, false));
+        Ok(())
+    }
+
+    fn leave_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        let Some(mut dir) = self.dirs.pop() else {
+            return Ok(());
+        };
+        let Some(mut entry) = dir.dir.take() else {
+            return Ok(());
+        };
+        let own_size = entry
+            .entry
+            .metadata()
+            .map(Metadata::len)
+            .unwrap_or_default();
+
+        let entry = match dir.entries.pop() {
+            // The line of the only entry is written in place of the directory
+            Some(mut only_entry) if dir.collapsed && dir.entries.is_empty() => {
+                let size = own_size + only_entry.sorted_entry().size() + dir.elided_size;
+                entry.entry.set_disk_usage(size);
+                only_entry.collapsed_dir = Some(entry.entry);
+                only_entry
+            }
+            last_entry => {
+                dir.entries.extend(last_entry);
+                let (lines, contents_size) = self.close_dir(dir);
+                entry.entry.set_disk_usage(own_size + contents_size);
+                entry.lines = lines;
+                entry
+            }
+        };
+
+        if let Some(parent) = self.dirs.last_mut() {
+            parent.entries.push(entry);
+        }
+        Ok(())
+    }
+
+    fn elided(&mut self, entries: &[&FilteredEntry], _depth: usize) -> Result<()> {
+        if let Some(dir) = self.dirs.last_mut() {
+            dir.elided += entries.len();
+            dir.elided_size += entries
+                .iter()
+                .map(|entry| entry.as_ref().size())
+                .sum::<u64>();
+        }
+        Ok(())
+    }
+
+    fn truncated(
+        &mut self,
+        _dir: &FilteredEntry,
+        dirs: usize,
+        files: usize,
+        _depth: usize,
+    ) -> Result<()> {
+        self.annotation = Some(truncated_annotation(dirs, files));
+        Ok(())
+    }
+
+    fn error(&mut self, _path: &Path, error: &Error, depth: usize) -> Result<()> {
+        if depth == 0 {
+            self.root_annotation = Some(annotation(error));
+        } else {
+            self.annotation = Some(Cow::Borrowed(annotation(error)));
+        }
+        Ok(())
+    }
+}
+
+/// Writes the line which follows the entries of a directory with more entries
+/// than --max-entries allows, e.g. "└── … 3 more entries".
+fn write_elided_line(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    prefix: &str,
+    count: usize,
+) -> std::io::Result<()> {
+    let corner = options.charset.corner();
+    let ellipsis = match options.charset {
+        Charset::Utf8 => "…",
+        Charset::Ascii => "...",
+    };
+    let noun = if count == 1 { "entry" } else { "entries" };
+    writeln!(w, "{prefix}{corner}{ellipsis} {count} more {noun}")
+}
+
+/// Returns the name which is printed for the entry, i.e. its file name, or its
+/// path with -f.
+fn entry_name<'e>(
+    options: &TreeOptions,
+    paths: Option<&PathDisplay>,
+    entry: &'e Entry,
+) -> Cow<'e, OsStr> {
+    match paths {
+        _ if !options.print_full_path_prefix => Cow::Borrowed(entry.file_name()),
+        Some(paths) => match paths.path(entry.path()) {
+            Cow::Borrowed(path) => Cow::Borrowed(path.as_os_str()),
+            Cow::Owned(path) => Cow::Owned(path.into_os_string()),
+        },
+        None => Cow::Borrowed(entry.path().as_os_str()),
+    }
+}
+
+/// Returns the indentation added for the children of an entry.
+#[inline]
+pub(crate) fn child_prefix(options: &TreeOptions, is_last: bool) -> &'static str {
+    if is_last {
+        "    "
+    } else {
+        options.charset.vertical()
+    }
+}
+
+/// Writes the line naming the root of a tree, followed by the annotation (if
+/// any), e.g. to show that the root could not be read.
+pub(crate) fn write_root_line(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    root: &Path,
+    name: &Path,
+    size: Option<u64>,
+    annotation: Option<&str>,
+) -> std::io::Result<()> {
+    let root_name = name.as_os_str().as_bytes();
+    let metadata = std::fs::metadata(root).ok();
+    if let Some(size) = size {
+        write_info(w, options, size)?;
+    }
+    write_hyperlink_start(w, options, root)?;
+    match options.colors.as_ref() {
+        Some(colors) => {
+            let start_code = colors.start_code(metadata.as_ref(), root_name, false, false);
+            write_colored(w, options, start_code, root_name, colors.end_code())?;
+        }
+        None => write_name(w, root_name, options.name_style())?,
+    }
+    write_hyperlink_end(w, options)?;
+    write_indicator(w, options, metadata.as_ref())?;
+    write_annotation(w, annotation)?;
+    writeln!(w)
+}
+
+/// Writes the line for a single entry of a tree, i.e. the indentation, icon,
+/// name and link target, followed by the annotation (if any), e.g. to show that
+/// the directory could not be read.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_entry_line(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    prefix: &str,
+    entry: &Entry,
+    size: u64,
+    is_last: bool,
+    name: &OsStr,
+    link_target: Option<&Path>,
+    annotation: Option<&str>,
+) -> std::io::Result<()> {
+    write!(w, "{prefix}{}", branch(options, is_last))?;
+    write_entry(w, options, entry, size, name, link_target, annotation)
+}
+
+/// Returns the branch which precedes the name of an entry.
+#[inline]
+fn branch(options: &TreeOptions, is_last: bool) -> &'static str {
+    if is_last {
+        options.charset.corner()
+    } else {
+        options.charset.branch()
+    }
+}
+
+/// Writes the line for a single entry of a tree after its indentation and
+/// branch (see [`write_entry_line`]).
+fn write_entry(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    entry: &Entry,
+    size: u64,
+    name: &OsStr,
+    link_target: Option<&Path>,
+    annotation: Option<&str>,
+) -> std::io::Result<()> {
+    write_info(w, options, size)?;
+    write!(w, "{}", icon(options, entry))?;
+    write_hyperlink_start(w, options, entry.path())?;
+
+    let Some(colors) = options.colors.as_ref() else {
+        write_name(w, name.as_bytes(), options.name_style())?;
+        write_hyperlink_end(w, options)?;
+        if let Some(link_target) = link_target {
+            write!(w, " -> ")?;
+            write_name(w, link_target.as_os_str().as_bytes(), options.name_style())?;
+        }
+        write_indicator(w, options, indicator_metadata(entry, link_target))?;
+        write_annotation(w, annotation)?;
+        return writeln!(w);
+    };
+
+    let orphan = entry.is_orphan();
+    let metadata = if colors.link_as_target() && !orphan {
+        entry.target_metadata()
+    } else {
+        entry.metadata()
+    };
+    let start_code = colors.start_code(metadata, entry.file_name().as_bytes(), orphan, false);
+    write_colored(w, options, start_code, name.as_bytes(), colors.end_code())?;
+    write_hyperlink_end(w, options)?;
+
+    if let Some(link_target) = link_target {
+        write!(w, " -> ")?;
+        let start_code = colors.start_code(
+            entry.target_metadata(),
+            link_target.as_os_str().as_bytes(),
+            orphan,
+            true,
+        );
+        write_colored(
+            w,
+            options,
+            start_code,
+            link_target.as_os_str().as_bytes(),
+            colors.end_code(),
+        )?;
+    }
+
+    write_indicator(w, options, indicator_metadata(entry, link_target))?;
+    write_annotation(w, annotation)?;
+    writeln!(w)
+}
+
+/// Returns the metadata which determines the indicator (-F) for an entry.
+#[inline]
+fn indicator_metadata<'a>(entry: &'a Entry, link_target: Option<&Path>) -> Option<&'a Metadata> {
+    if link_target.is_some() {
+        entry.target_metadata()
+    } else {
+        entry.metadata()
+    }
+}
+
+/// Writes the metadata bracket which precedes the name of an entry, e.g.
+/// `[       4096]  ` with -s.
+#[inline]
+pub(crate) fn write_info(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    size: u64,
+) -> std::io::Result<()> {
+    if !options.print_size {
+        return Ok(());
+    }
+
+    let info = format_size(size, options.size_units);
+    write!(w, "[{}]  ", &info[1..])
+}
+
+#[inline]
+fn write_hyperlink_start(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    path: &Path,
+) -> std::io::Result<()> {
+    match options.hyperlinks.as_ref() {
+        Some(hyperlinks) => hyperlinks.write_start(w, path),
+        None => Ok(()),
+    }
+}
+
+#[inline]
+fn write_hyperlink_end(w: &mut impl Write, options: &TreeOptions) -> std::io::Result<()> {
+    match options.hyperlinks.as_ref() {
+        Some(hyperlinks) => hyperlinks.write_end(w),
+        None => Ok(()),
+    }
+}
+
+#[inline]
+fn write_annotation(w: &mut impl Write, annotation: Option<&str>) -> std::io::Result<()> {
+    if let Some(annotation) = annotation {
+        write!(w, "  [{annotation}]")?;
+    }
+    Ok(())
+}
+
+/// Writes the character which indicates the type of file with the given
+/// metadata (-F), as per `ls -F`. Like the reference implementation, symlinks
+/// are indicated by the type of their target, which is written after the
+/// target.
+#[inline]
+pub(crate) fn write_indicator(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    metadata: Option<&Metadata>,
+) -> std::io::Result<()> {
+    if !options.classify {
+        return Ok(());
+    }
+    let Some(metadata) = metadata else {
+        return Ok(());
+    };
+
+    let indicator = match FileKind::from(metadata.file_type()) {
+        FileKind::Directory if !options.list_directories_only => "/",
+        FileKind::Socket => "=",
+        FileKind::Fifo => "|",
+        FileKind::Symlink => "@",
+        FileKind::File if metadata.mode() & 0o111 != 0 => "*",
+        _ => return Ok(()),
+    };
+
+    write!(w, "{indicator}")
+}
+
+#[inline]
+fn write_colored(
+    w: &mut impl Write,
+    options: &TreeOptions,
+    start_code: Option<String>,
+    name: &[u8],
+    end_code: &str,
+) -> std::io::Result<()> {
+    match start_code {
+        Some(start_code) => {
+            write!(w, "{start_code}")?;
+            write_name(w, name, options.name_style())?;
+            write!(w, "{end_code}")
+        }
+        None => write_name(w, name, options.name_style()),
+    }
+}
+
+#[inline]
+fn icon(options: &TreeOptions, entry: &Entry) -> &'static str {
+    if !options.icons {
+        return ICON_NONE;
+    }
+    if entry.file_type().is_dir() {
+        ICON_DIR
+    } else {
+        if let Some(file_name) = entry.file_name().to_str() {
+            if let Some((icon, _)) = ICONS_BY_FILENAME.get(file_name) {
+                return icon;
+            }
+            let mut parts = file_name.split('.').rev();
+            let short_extension = unsafe {
+                // SAFETY: Iterator will always contain at least one element
+                parts.next().unwrap_unchecked()
+            };
+            if let Some(part) = parts.next()
+                && let Some((icon, _)) =
+                    ICONS_BY_EXTENSION.get(&format!("{part}.{short_extension}"))
+            {
+                return icon;
+            }
+            if let Some((icon, _)) = ICONS_BY_EXTENSION.get(short_extension) {
+                return icon;
+            }
+        }
+        ICON_TEXT
+    }
+}
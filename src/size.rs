@@ -0,0 +1,40 @@
+/// The units in which sizes are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeUnits {
+    /// Print sizes in bytes (-s).
+    #[default]
+    Bytes,
+    /// Print sizes in a more human readable way, using powers of 1024 (-h).
+    Binary,
+    /// Like [`SizeUnits::Binary`], but using powers of 1000 (--si).
+    Si,
+}
+
+/// Formats a size in the same way as the reference implementation (see `psize`
+/// in tree), including the leading space.
+pub(crate) fn format_size(size: u64, units: SizeUnits) -> String {
+    let (unit_size, unit_names) = match units {
+        SizeUnits::Bytes => return format!(" {size:11}"),
+        SizeUnits::Binary => (1024, b"BKMGTPEZY"),
+        SizeUnits::Si => (1000, b"dkMGTPEZY"),
+    };
+
+    let mut size = size;
+    let mut index = if size < unit_size { 0 } else { 1 };
+    while size >= unit_size * unit_size {
+        index += 1;
+        size /= unit_size;
+    }
+
+    if index == 0 {
+        return format!(" {size:4}");
+    }
+
+    let unit = unit_names[index] as char;
+    let value = size as f32 / unit_size as f32;
+    if size / unit_size >= 10 {
+        format!(" {value:3.0}{unit}")
+    } else {
+        format!(" {value:3.1}{unit}")
+    }
+}
@@ -2,6 +2,82 @@ use std::cmp::Ordering;
 
 use crate::entry::Entry;
 
+/// Compares two entries of the same directory.
+///
+/// Comparisons are given the entries themselves, so they can use the metadata
+/// of the entries (see [`Entry::metadata`]) and the sizes of directories
+/// computed with --du (see [`Entry::size`]).
+pub type CompareFn = fn(&Entry, &Entry) -> Ordering;
+
+/// The built-in orders of entries (--sort).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortType {
+    /// Sort by name (default).
+    #[default]
+    Name,
+    /// Sort by size, largest first. With --du, directories are sorted by the
+    /// total size of their contents.
+    Size,
+}
+
+impl SortType {
+    /// Returns the sort type with the given name, as accepted by --sort.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(Self::Name),
+            "size" => Some(Self::Size),
+            _ => None,
+        }
+    }
+}
+
+/// The order in which the entries of each directory are listed.
+#[derive(Debug, Clone, Copy)]
+pub struct Sorter {
+    compare: CompareFn,
+    reverse: bool,
+}
+
+impl Sorter {
+    /// Creates a sorter which orders entries using the given comparison.
+    pub fn new(compare: CompareFn) -> Self {
+        Self {
+            compare,
+            reverse: false,
+        }
+    }
+
+    /// Creates a sorter for one of the built-in orders.
+    pub fn by(sort_type: SortType) -> Self {
+        match sort_type {
+            SortType::Name => Self::new(default_sorter),
+            SortType::Size => Self::new(size_sorter),
+        }
+    }
+
+    /// Reverses the order of the sort (-r).
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    #[inline]
+    pub fn compare(&self, a: &Entry, b: &Entry) -> Ordering {
+        let ordering = (self.compare)(a, b);
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl Default for Sorter {
+    fn default() -> Self {
+        Self::by(SortType::Name)
+    }
+}
+
 pub fn default_sorter(a: &Entry, b: &Entry) -> Ordering {
     a.file_name().cmp(b.file_name())
 
@@ -15,3 +91,9 @@ pub fn default_sorter(a: &Entry, b: &Entry) -> Ordering {
     //     _ => a.file_name().cmp(&b.file_name()),
     // }
 }
+
+/// Sorts larger entries first, and entries of the same size by name, like the
+/// reference implementation.
+pub fn size_sorter(a: &Entry, b: &Entry) -> Ordering {
+    b.size().cmp(&a.size()).then_with(|| default_sorter(a, b))
+}
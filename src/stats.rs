@@ -1,16 +1,59 @@
 use std::{io::Write, sync::Arc};
 
-use crate::options::TreeOptions;
+use crate::{
+    error::{Error, Result},
+    options::TreeOptions,
+    size::{SizeUnits, format_size},
+};
 
+/// Receives a count of each directory and file listed in a tree.
 pub trait TreeStats {
     fn count_dir(&mut self);
     fn count_file(&mut self);
+    /// Counts a directory which could not be read.
+    fn count_error(&mut self) {}
+    /// Counts an entry which was not listed because of --max-entries.
+    fn count_elided(&mut self, _is_dir: bool) {}
+    /// Counts the entries below a directory which was not descended into
+    /// because of -L (--show-truncated).
+    fn count_truncated(&mut self, _dirs: usize, _files: usize) {}
 }
 
+impl<S: TreeStats + ?Sized> TreeStats for &mut S {
+    #[inline(always)]
+    fn count_dir(&mut self) {
+        (**self).count_dir();
+    }
+
+    #[inline(always)]
+    fn count_file(&mut self) {
+        (**self).count_file();
+    }
+
+    #[inline(always)]
+    fn count_error(&mut self) {
+        (**self).count_error();
+    }
+
+    #[inline(always)]
+    fn count_elided(&mut self, is_dir: bool) {
+        (**self).count_elided(is_dir);
+    }
+
+    #[inline(always)]
+    fn count_truncated(&mut self, dirs: usize, files: usize) {
+        (**self).count_truncated(dirs, files);
+    }
+}
+
+/// Counts directories and files to produce the report printed by the tree
+/// command, e.g. "3 directories, 6 files".
 pub struct DefaultTreeStats {
     options: Arc<TreeOptions>,
     dirs: usize,
     files: usize,
+    errors: usize,
+    disk_usage: u64,
 }
 
 impl TreeStats for DefaultTreeStats {
@@ -23,6 +66,38 @@ impl TreeStats for DefaultTreeStats {
     fn count_file(&mut self) {
         self.files += 1;
     }
+
+    #[inline(always)]
+    fn count_error(&mut self) {
+        self.errors += 1;
+    }
+
+    /// Elided entries are only counted if
+    /// [`count_elided_entries`](crate::options::TreeOptionsBuilder::count_elided_entries)
+    /// is set.
+    #[inline(always)]
+    fn count_elided(&mut self, is_dir: bool) {
+        if !self.options.count_elided_entries {
+            return;
+        }
+
+        if is_dir {
+            self.count_dir();
+        } else {
+            self.count_file();
+        }
+    }
+
+    /// Truncated entries are only counted if
+    /// [`count_truncated_entries`](crate::options::TreeOptionsBuilder::count_truncated_entries)
+    /// is set.
+    #[inline(always)]
+    fn count_truncated(&mut self, dirs: usize, files: usize) {
+        if self.options.count_truncated_entries {
+            self.dirs += dirs;
+            self.files += files;
+        }
+    }
 }
 
 impl DefaultTreeStats {
@@ -31,6 +106,8 @@ impl DefaultTreeStats {
             options,
             dirs: 0,
             files: 0,
+            errors: 0,
+            disk_usage: 0,
         }
     }
 
@@ -44,7 +121,39 @@ impl DefaultTreeStats {
         self.files
     }
 
-    pub fn write(&self, w: &mut impl Write) -> anyhow::Result<()> {
+    /// The number of directories which could not be read.
+    #[inline(always)]
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// Adds the size of a root to the total size reported with --du.
+    #[inline(always)]
+    pub fn count_disk_usage(&mut self, size: u64) {
+        self.disk_usage += size;
+    }
+
+    /// The total size of the roots (--du).
+    #[inline(always)]
+    pub fn disk_usage(&self) -> u64 {
+        self.disk_usage
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> Result<()> {
+        self.write_report(w).map_err(Error::Write)
+    }
+
+    fn write_report(&self, w: &mut impl Write) -> std::io::Result<()> {
+        if self.options.disk_usage {
+            let size = format_size(self.disk_usage, self.options.size_units);
+            let bytes = if self.options.size_units == SizeUnits::Bytes {
+                " bytes"
+            } else {
+                ""
+            };
+            write!(w, "{size}{bytes} used in ")?;
+        }
+
         if self.options.list_directories_only {
             match self.dirs() {
                 1 => writeln!(w, "1 directory, 1 file"),
@@ -64,12 +173,13 @@ impl DefaultTreeStats {
         Ok(())
     }
 
-    pub fn print(&self) -> anyhow::Result<()> {
+    pub fn print(&self) -> Result<()> {
         let mut writer = std::io::stdout();
         self.write(&mut writer)
     }
 }
 
+/// Ignores all counts, for use when no report is needed.
 pub struct NoopTreeStats;
 
 impl TreeStats for NoopTreeStats {
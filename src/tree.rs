@@ -1,45 +1,47 @@
 use std::{
-    cmp::Ordering,
-    fs::read_link,
+    cell::RefCell,
+    collections::HashSet,
+    fs::{Metadata, read_link},
     io::Write,
+    os::unix::fs::MetadataExt as _,
     path::{Path, PathBuf},
+    rc::Rc,
     sync::Arc,
 };
 
-use anyhow::Context as _;
-
 use crate::{
     entry::Entry,
+    error::{Error, Result},
     filter::{FilteredEntry, TreeFilter},
-    icons::*,
+    full_path::PathDisplay,
+    model::Node,
     options::TreeOptions,
+    render::text::{DiskUsageWriter, TextWriter, write_root_line},
     stats::TreeStats,
+    visitor::TreeVisitor,
 };
 
+/// A directory tree which can be written in the same format as the tree
+/// command.
 pub struct Tree<'tree> {
     filter: TreeFilter<'tree>,
     options: Arc<TreeOptions>,
     depth: usize,
-    prefix: String,
-    path_prefix: Option<PathBuf>,
     root: PathBuf,
+    /// The device and inode numbers of the directories which have been entered
+    /// by following symlinks (-l), to avoid following recursive links.
+    followed_links: Rc<RefCell<HashSet<(u64, u64)>>>,
 }
 
 impl<'tree> Tree<'tree> {
-    pub fn new(root: PathBuf, options: Arc<TreeOptions>) -> anyhow::Result<Self> {
-        let path_prefix = if options.print_full_path_prefix {
-            Some(root.clone())
-        } else {
-            None
-        };
-
+    /// Creates a tree rooted at the given directory.
+    pub fn new(root: PathBuf, options: Arc<TreeOptions>) -> Result<Self> {
         Ok(Self {
             filter: TreeFilter::new(&root, &options)?,
             options,
             depth: 0,
-            prefix: "".to_string(),
-            path_prefix,
             root,
+            followed_links: Default::default(),
         })
     }
 
@@ -47,151 +49,407 @@ impl<'tree> Tree<'tree> {
         &self.root
     }
 
-    fn enter_dir(&'tree self, dir: FilteredEntry, is_last: bool) -> anyhow::Result<Self> {
-        let FilteredEntry {
-            filter_state,
-            entry: dir,
-        } = dir;
-        let new_prefix = if is_last { "    " } else { "│   " };
+    fn enter_dir(&'tree self, dir: &FilteredEntry) -> Result<Self> {
         Ok(Tree {
             filter: self
                 .filter
-                .enter_dir(&dir, &self.options, filter_state)
-                .with_context(|| format!("Failed to enter {}", dir.path().to_string_lossy()))?,
+                .enter_dir(dir.as_ref(), &self.options, dir.filter_state.clone())?,
             options: self.options.clone(),
             depth: self.depth + 1,
-            prefix: format!("{}{}", self.prefix, new_prefix),
-            path_prefix: self
-                .path_prefix
-                .as_ref()
-                .map(|path_prefix| path_prefix.join(dir.file_name())),
-            root: dir.into_path(),
+            root: dir.as_ref().path().to_path_buf(),
+            followed_links: self.followed_links.clone(),
         })
     }
 
-    #[inline]
-    fn write_entry(
-        &self,
-        w: &mut impl Write,
-        entry: FilteredEntry,
-        is_last: bool,
-        stats: &mut impl TreeStats,
-    ) -> anyhow::Result<()> {
-        let file_name = match self.path_prefix.as_ref() {
-            Some(path_prefix) => path_prefix
-                .join(entry.as_ref().file_name())
-                .to_string_lossy()
-                .to_string(),
-            None => entry.as_ref().file_name().to_string_lossy().to_string(),
-        };
-        let link_target = if entry.as_ref().file_type().is_symlink() {
-            let target = read_link(entry.as_ref().path()).context("Failed to read link")?;
-            format!(" -> {}", target.to_string_lossy())
-        } else {
-            "".to_string()
+    /// Reads the entries of the root directory, and returns those which pass
+    /// the filter, in sorted order.
+    fn entries(&self) -> Result<Vec<FilteredEntry>> {
+        let read_dir_error = |source| Error::ReadDir {
+            path: self.root.clone(),
+            source,
         };
-        let icon = self.icon(entry.as_ref());
-        let result = if is_last {
-            writeln!(w, "{}└── {icon}{file_name}{link_target}", self.prefix)
+        let mut entries = std::fs::read_dir(&self.root)
+            .map_err(read_dir_error)?
+            .map(|entry| -> Result<Entry> { Entry::new(entry.map_err(read_dir_error)?) })
+            .filter_map(|entry_result| match entry_result {
+                Err(err) => Some(Err(err)),
+                Ok(entry) => self.filter.filter(entry, &self.options).map(Ok),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        entries.sort_by(|a, b| self.options.sorter.compare(a.as_ref(), b.as_ref()));
+
+        Ok(entries)
+    }
+
+    /// Enters the given directory, and reads its entries.
+    fn read_dir(&'tree self, dir: &FilteredEntry) -> Result<(Self, Vec<FilteredEntry>)> {
+        if dir.as_ref().file_type().is_symlink()
+            && let Some(metadata) = dir.as_ref().target_metadata()
+            && !self
+                .followed_links
+                .borrow_mut()
+                .insert((metadata.dev(), metadata.ino()))
+        {
+            return Err(Error::RecursiveLink {
+                path: dir.as_ref().path().to_path_buf(),
+            });
+        }
+
+        let dir = self.enter_dir(dir)?;
+        let entries = dir.entries()?;
+        Ok((dir, entries))
+    }
+
+    /// Whether the entries of the given entry should be listed.
+    #[inline]
+    fn should_enter_dir(&self, entry: &Entry) -> bool {
+        !self.is_at_max_level() && self.is_enterable(entry)
+    }
+
+    /// Whether the entries of this directory are the last level listed (-L).
+    #[inline]
+    fn is_at_max_level(&self) -> bool {
+        self.options
+            .max_level
+            .is_some_and(|max_level| max_level - 1 <= self.depth)
+    }
+
+    /// Whether the entry is a directory which can be descended into, i.e. not
+    /// a symlink unless symlinks are followed (-l).
+    #[inline]
+    fn is_enterable(&self, entry: &Entry) -> bool {
+        if entry.file_type().is_symlink() {
+            self.options.follow_links && entry.target_is_dir()
         } else {
-            writeln!(w, "{}├── {icon}{file_name}{link_target}", self.prefix)
-        };
-        result.context("Failed to write entry")?;
+            entry.file_type().is_dir()
+        }
+    }
+
+    /// Counts the directories and files below the given directory, which is
+    /// not descended into because of -L, which would otherwise be listed
+    /// (--show-truncated). Returns `None` if the option isn't set, or if the
+    /// directory can't be read.
+    fn count_truncated(&self, dir: &FilteredEntry) -> Option<(usize, usize)> {
+        if !self.options.show_truncated
+            || !self.is_at_max_level()
+            || !self.is_enterable(dir.as_ref())
+        {
+            return None;
+        }
+
+        // Links followed while counting may still be listed elsewhere, so they
+        // are forgotten afterwards
+        let followed_links = self.followed_links.borrow().clone();
+        let counts = self
+            .read_dir(dir)
+            .ok()
+            .map(|(dir, entries)| dir.count_entries(entries));
+        *self.followed_links.borrow_mut() = followed_links;
+
+        counts
+    }
+
+    /// Counts the given entries of this directory, and the entries below them,
+    /// regardless of -L and --max-entries.
+    fn count_entries(&self, entries: Vec<FilteredEntry>) -> (usize, usize) {
+        let (mut dirs, mut files) = (0, 0);
+
+        for entry in entries {
+            if !entry.as_ref().target_is_dir() {
+                files += 1;
+                continue;
+            }
 
-        if entry.as_ref().file_type().is_dir() {
-            stats.count_dir();
-            let should_enter_dir = if let Some(max_level) = self.options.max_level {
-                max_level - 1 > self.depth
+            let below = if self.is_enterable(entry.as_ref()) {
+                self.read_dir(&entry)
+                    .ok()
+                    .map(|(dir, entries)| dir.count_entries(entries))
             } else {
-                true
+                None
             };
-            if should_enter_dir {
-                self.enter_dir(entry, is_last)?.write(w, stats)?;
+
+            // See Tree::should_prune
+            if self.options.prune
+                && below == Some((0, 0))
+                && (self.options.predicates.is_empty()
+                    || !self.options.predicates.matches(entry.as_ref()))
+            {
+                continue;
             }
-        } else {
-            stats.count_file();
+
+            let (below_dirs, below_files) = below.unwrap_or_default();
+            dirs += 1 + below_dirs;
+            files += below_files;
         }
 
-        Ok(())
+        (dirs, files)
     }
 
-    pub fn write(&self, w: &mut impl Write, stats: &mut impl TreeStats) -> anyhow::Result<()> {
-        let mut entries = std::fs::read_dir(&self.root)
-            .context("Failed to read directory")?
-            .map(|entry| -> anyhow::Result<Entry> { Entry::new(entry?) })
-            .filter_map(|entry_result| match entry_result {
-                Err(err) => Some(Err(err)),
-                Ok(entry) => self.filter.filter(entry, &self.options).map(Ok),
+    #[inline]
+    fn link_target(entry: &Entry) -> Result<Option<PathBuf>> {
+        if !entry.file_type().is_symlink() {
+            return Ok(None);
+        }
+
+        read_link(entry.path())
+            .map(Some)
+            .map_err(|source| Error::ReadLink {
+                path: entry.path().to_path_buf(),
+                source,
             })
-            .collect::<Vec<_>>();
+    }
 
-        // Don't ask... for some reason tree counts the root dir, but only if it
-        // is not empty.
-        if self.depth == 0 && !entries.is_empty() {
-            stats.count_dir();
+    /// Visits the entries of the tree, recursively, in the order in which they
+    /// are listed. The root itself is not visited, but if it can't be read the
+    /// error is passed to the visitor with a depth of 0.
+    pub fn visit(&self, visitor: &mut impl TreeVisitor) -> Result<()> {
+        self.followed_links.borrow_mut().clear();
+        match self.entries() {
+            // Whether a directory is pruned is only known once its contents
+            // have been read, so the tree is built in memory first (--prune).
+            // Likewise for the entries which are elided when the entries are
+            // sorted by the sizes of their contents (--du and --max-entries).
+            Ok(entries)
+                if self.options.prune
+                    || (self.options.disk_usage && self.options.max_entries.is_some()) =>
+            {
+                self.build_root(Ok(entries))?.visit(visitor)
+            }
+            Ok(entries) => self.visit_entries(entries, visitor),
+            Err(err) => visitor.error(&self.root, &err, self.depth),
         }
+    }
+
+    fn visit_entries(
+        &self,
+        mut entries: Vec<FilteredEntry>,
+        visitor: &mut impl TreeVisitor,
+    ) -> Result<()> {
+        let depth = self.depth + 1;
+        let elided = match self.options.max_entries {
+            Some(max_entries) if entries.len() > max_entries => entries.split_off(max_entries),
+            _ => Vec::new(),
+        };
+        let len = entries.len();
 
-        entries.sort_by(|a, b| match (a, b) {
-            (Ok(a), Ok(b)) => (self.options.sorter)(a.as_ref(), b.as_ref()),
-            (Err(_), Err(_)) => Ordering::Equal,
-            (Ok(_), Err(_)) => Ordering::Greater,
-            (Err(_), Ok(_)) => Ordering::Less,
-        });
+        for (index, entry) in entries.into_iter().enumerate() {
+            let is_last = index + 1 == len && elided.is_empty();
+            let link_target = Self::link_target(entry.as_ref())?;
 
-        if let (Some(last_entry), leading_entries) = (entries.pop(), entries) {
-            for entry in leading_entries.into_iter() {
-                self.write_entry(w, entry?, false, stats)?;
+            if !self.should_enter_dir(entry.as_ref()) {
+                if let Some((dirs, files)) = self.count_truncated(&entry) {
+                    visitor.truncated(&entry, dirs, files, depth)?;
+                }
+                visitor.entry(&entry, link_target.as_deref(), depth, is_last)?;
+                continue;
+            }
+
+            // The directory is read before it is visited, so that visitors can
+            // annotate the entry if it can't be read.
+            match self.read_dir(&entry) {
+                Ok((dir, dir_entries)) => {
+                    if link_target.is_none()
+                        && let [child] = &dir_entries[..]
+                        && dir.should_enter_dir(child.as_ref())
+                    {
+                        visitor.collapsed_dir(&entry, depth, is_last)?;
+                    } else {
+                        visitor.entry(&entry, link_target.as_deref(), depth, is_last)?;
+                        visitor.enter_dir(&entry, depth, is_last)?;
+                    }
+                    dir.visit_entries(dir_entries, visitor)?;
+                    visitor.leave_dir(&entry, depth, is_last)?;
+                }
+                Err(err) => {
+                    visitor.error(entry.as_ref().path(), &err, depth)?;
+                    visitor.entry(&entry, link_target.as_deref(), depth, is_last)?;
+                }
             }
-            self.write_entry(w, last_entry?, true, stats)?;
+        }
+
+        if !elided.is_empty() {
+            visitor.elided(&elided.iter().collect::<Vec<_>>(), depth)?;
         }
 
         Ok(())
     }
 
-    pub fn write_root(&self, w: &mut impl Write) -> anyhow::Result<()> {
-        writeln!(w, "{}", self.root.to_string_lossy())?;
-        Ok(())
+    /// Writes the entries of the tree, recursively, counting each entry in
+    /// `stats`. The root itself is not written (see [`Tree::write_root`]).
+    ///
+    /// The sizes of directories are not computed from their contents, even
+    /// with --du (see [`Tree::write_disk_usage`]).
+    pub fn write(&self, w: &mut impl Write, stats: &mut impl TreeStats) -> Result<()> {
+        let paths = PathDisplay::new(&self.options.full_path, &self.root)?;
+        let writer = TextWriter::new(w, self.options.clone()).with_paths(paths);
+        self.visit(&mut (writer, stats))
+    }
+
+    /// Writes the tree, including its root, with the size of each directory
+    /// computed from its contents (--du), counting each entry in `stats`.
+    /// Returns the size of the root.
+    pub fn write_disk_usage(&self, w: &mut impl Write, stats: &mut impl TreeStats) -> Result<u64> {
+        let paths = PathDisplay::new(&self.options.full_path, &self.root)?;
+        let mut visitor = (DiskUsageWriter::new(self.options.clone(), paths), stats);
+        self.visit(&mut visitor)?;
+        visitor.0.finish(w, &self.root)
+    }
+
+    /// Writes the line naming the root of the tree. Like the tree command, the
+    /// line is annotated if the root can't be read.
+    pub fn write_root(&self, w: &mut impl Write) -> Result<()> {
+        let annotation = std::fs::read_dir(&self.root)
+            .is_err()
+            .then_some("error opening dir");
+        let size = std::fs::metadata(&self.root)
+            .ok()
+            .map(|metadata| metadata.len());
+        let paths = PathDisplay::new(&self.options.full_path, &self.root)?;
+        write_root_line(
+            w,
+            &self.options,
+            &self.root,
+            paths.display_root(),
+            size,
+            annotation,
+        )
+        .map_err(Error::Write)
     }
 
-    pub fn print(&self, stats: &mut impl TreeStats) -> anyhow::Result<()> {
+    pub fn print(&self, stats: &mut impl TreeStats) -> Result<()> {
         let mut writer = std::io::stdout();
         self.write(&mut writer, stats)
     }
 
-    pub fn print_root(&self) -> anyhow::Result<()> {
+    pub fn print_root(&self) -> Result<()> {
         let mut writer = std::io::stdout();
         self.write_root(&mut writer)
     }
 
-    #[inline]
-    fn icon(&self, entry: &Entry) -> &'static str {
-        if !self.options.icons {
-            return ICON_NONE;
+    /// Builds an in-memory model of the tree, honouring all of the options
+    /// (filtering, sorting, max level etc.), which can be inspected or
+    /// post-processed before being rendered. Directories which can't be read
+    /// have no children, and the error is recorded in the node.
+    pub fn build(&self) -> Result<Node> {
+        self.followed_links.borrow_mut().clear();
+        self.build_root(self.entries())
+    }
+
+    fn build_root(&self, entries: Result<Vec<FilteredEntry>>) -> Result<Node> {
+        let root = FilteredEntry {
+            filter_state: Default::default(),
+            entry: Entry::root(self.root.clone())?,
+        };
+
+        let (children, error) = match entries {
+            Ok(entries) => (Some(self.build_children(entries)?), None),
+            Err(err) => (None, Some(err)),
+        };
+
+        let mut root = Node {
+            entry: root,
+            link_target: None,
+            children: None,
+            elided: Vec::new(),
+            truncated: None,
+            error,
+        };
+        if let Some(children) = children {
+            self.set_children(&mut root, children);
         }
-        if entry.file_type().is_dir() {
-            ICON_DIR
-        } else {
-            if let Some(file_name) = entry.file_name().to_str() {
-                if let Some((icon, _)) = ICONS_BY_FILENAME.get(file_name) {
-                    return icon;
-                }
-                let mut parts = file_name.split('.').rev();
-                let short_extension = unsafe {
-                    // SAFETY: Iterator will always contain at least one element
-                    parts.next().unwrap_unchecked()
-                };
-                if let Some(part) = parts.next() {
-                    if let Some((icon, _)) =
-                        ICONS_BY_EXTENSION.get(&format!("{part}.{short_extension}"))
-                    {
-                        return icon;
-                    }
-                }
-                if let Some((icon, _)) = ICONS_BY_EXTENSION.get(short_extension) {
-                    return icon;
-                }
+        self.roll_up_disk_usage(&root);
+
+        Ok(root)
+    }
+
+    fn build_children(&self, entries: Vec<FilteredEntry>) -> Result<Vec<Node>> {
+        let mut children = entries
+            .into_iter()
+            .map(|entry| self.build_node(entry))
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.options.prune {
+            children.retain(|child| !self.should_prune(child));
+        }
+
+        // The sizes of directories are only known once their contents have
+        // been rolled up, so the entries are sorted again (--du).
+        if self.options.disk_usage {
+            children.sort_by(|a, b| self.options.sorter.compare(a.entry(), b.entry()));
+        }
+
+        Ok(children)
+    }
+
+    fn build_node(&self, entry: FilteredEntry) -> Result<Node> {
+        let link_target = Self::link_target(entry.as_ref())?;
+        let mut node = Node {
+            entry,
+            link_target,
+            children: None,
+            elided: Vec::new(),
+            truncated: None,
+            error: None,
+        };
+
+        if !self.should_enter_dir(node.entry.as_ref()) {
+            node.truncated = self.count_truncated(&node.entry);
+            return Ok(node);
+        }
+
+        match self.read_dir(&node.entry) {
+            Ok((dir, entries)) => {
+                let children = dir.build_children(entries)?;
+                self.set_children(&mut node, children);
             }
-            ICON_TEXT
+            Err(err) => node.error = Some(err),
+        }
+        self.roll_up_disk_usage(&node);
+
+        Ok(node)
+    }
+
+    /// Whether the node is a directory which is pruned (--prune), because
+    /// nothing is listed in it (once its own contents have been pruned), and it
+    /// doesn't match the type, size and time filters itself. Directories which
+    /// were not read, e.g. because of -L, are not pruned.
+    #[inline]
+    fn should_prune(&self, node: &Node) -> bool {
+        node.children().is_some_and(<[Node]>::is_empty)
+            && node.elided().is_empty()
+            && (self.options.predicates.is_empty()
+                || !self.options.predicates.matches(node.entry()))
+    }
+
+    /// Sets the children of the node, eliding those after the first
+    /// --max-entries.
+    #[inline]
+    fn set_children(&self, node: &mut Node, mut children: Vec<Node>) {
+        if let Some(max_entries) = self.options.max_entries
+            && children.len() > max_entries
+        {
+            node.elided = children.split_off(max_entries);
+        }
+        node.children = Some(children);
+    }
+
+    /// Adds the sizes of the children of the node to its own size (--du). The
+    /// children must already have been rolled up.
+    #[inline]
+    fn roll_up_disk_usage(&self, node: &Node) {
+        if !self.options.disk_usage {
+            return;
+        }
+
+        if let Some(children) = node.children() {
+            let own_size = node.metadata().map(Metadata::len).unwrap_or_default();
+            let children_size = children
+                .iter()
+                .chain(node.elided())
+                .map(|child| child.entry().size())
+                .sum::<u64>();
+            node.entry().set_disk_usage(own_size + children_size);
         }
     }
 }
@@ -0,0 +1,202 @@
+use std::path::Path;
+
+use crate::{
+    error::{Error, Result},
+    filter::FilteredEntry,
+    stats::TreeStats,
+};
+
+/// Receives the entries of a tree as it is traversed, in the order in which
+/// they are listed. See [`Tree::visit`](crate::tree::Tree::visit).
+///
+/// The root of the tree is not passed to the visitor, so the direct children of
+/// the root have a depth of 1. For each directory which is descended into,
+/// [`entry`](TreeVisitor::entry) is followed by
+/// [`enter_dir`](TreeVisitor::enter_dir), the entries of the directory, and
+/// finally [`leave_dir`](TreeVisitor::leave_dir).
+///
+/// Directories which can't be read are reported to
+/// [`error`](TreeVisitor::error), and the traversal continues. Returning an
+/// error from any of the callbacks stops the traversal.
+pub trait TreeVisitor {
+    /// Called for each entry of the tree. `is_last` is true if the entry is the
+    /// last entry in its directory.
+    fn entry(
+        &mut self,
+        entry: &FilteredEntry,
+        link_target: Option<&Path>,
+        depth: usize,
+        is_last: bool,
+    ) -> Result<()>;
+
+    /// Called instead of [`entry`](TreeVisitor::entry) and
+    /// [`enter_dir`](TreeVisitor::enter_dir) for a directory whose only entry
+    /// is a directory which is descended into, so that the two can be written
+    /// on a single line (--collapse). Directories which are symlinks are never
+    /// collapsed. [`leave_dir`](TreeVisitor::leave_dir) is still called for
+    /// the directory.
+    fn collapsed_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        self.entry(dir, None, depth, is_last)?;
+        self.enter_dir(dir, depth, is_last)
+    }
+
+    /// Called before the entries of a directory are visited.
+    fn enter_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after the entries of a directory have been visited.
+    fn leave_dir(&mut self, _dir: &FilteredEntry, _depth: usize, _is_last: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after the entries of a directory which has more entries than
+    /// --max-entries allows, with the entries which were not visited. `depth`
+    /// is the depth of the entries.
+    fn elided(&mut self, _entries: &[&FilteredEntry], _depth: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for each directory which is not descended into because of -L,
+    /// before the directory itself is passed to [`entry`](TreeVisitor::entry),
+    /// with the number of directories and files below it which would otherwise
+    /// be listed (--show-truncated). Not called if the directory can't be
+    /// read.
+    fn truncated(
+        &mut self,
+        _dir: &FilteredEntry,
+        _dirs: usize,
+        _files: usize,
+        _depth: usize,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the directory at `path` can't be read, before the directory
+    /// itself is passed to [`entry`](TreeVisitor::entry). The directory is not
+    /// entered. `depth` is the depth of the directory, which is 0 for the root.
+    fn error(&mut self, _path: &Path, _error: &Error, _depth: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Stats are visitors which count the entries of the tree.
+impl<S: TreeStats> TreeVisitor for S {
+    #[inline]
+    fn entry(
+        &mut self,
+        entry: &FilteredEntry,
+        _link_target: Option<&Path>,
+        depth: usize,
+        is_last: bool,
+    ) -> Result<()> {
+        // Don't ask... for some reason tree counts the root dir, but only if it
+        // is not empty. The root is not empty if and only if it has a last
+        // entry.
+        if depth == 1 && is_last {
+            self.count_dir();
+        }
+
+        if entry.as_ref().target_is_dir() {
+            self.count_dir();
+        } else {
+            self.count_file();
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn elided(&mut self, entries: &[&FilteredEntry], depth: usize) -> Result<()> {
+        // The root is not empty, even if all of its entries were elided (see
+        // above).
+        if depth == 1 {
+            self.count_dir();
+        }
+
+        for entry in entries {
+            self.count_elided(entry.as_ref().target_is_dir());
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn truncated(
+        &mut self,
+        _dir: &FilteredEntry,
+        dirs: usize,
+        files: usize,
+        _depth: usize,
+    ) -> Result<()> {
+        self.count_truncated(dirs, files);
+        Ok(())
+    }
+
+    #[inline]
+    fn error(&mut self, _path: &Path, error: &Error, _depth: usize) -> Result<()> {
+        // Recursive links are skipped deliberately, so they aren't errors
+        if !matches!(error, Error::RecursiveLink { .. }) {
+            self.count_error();
+        }
+        Ok(())
+    }
+}
+
+/// Visits each entry with both visitors, e.g. to write a tree while counting
+/// its entries.
+impl<A: TreeVisitor, B: TreeVisitor> TreeVisitor for (A, B) {
+    #[inline]
+    fn entry(
+        &mut self,
+        entry: &FilteredEntry,
+        link_target: Option<&Path>,
+        depth: usize,
+        is_last: bool,
+    ) -> Result<()> {
+        self.0.entry(entry, link_target, depth, is_last)?;
+        self.1.entry(entry, link_target, depth, is_last)
+    }
+
+    #[inline]
+    fn collapsed_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        self.0.collapsed_dir(dir, depth, is_last)?;
+        self.1.collapsed_dir(dir, depth, is_last)
+    }
+
+    #[inline]
+    fn enter_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        self.0.enter_dir(dir, depth, is_last)?;
+        self.1.enter_dir(dir, depth, is_last)
+    }
+
+    #[inline]
+    fn leave_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        self.0.leave_dir(dir, depth, is_last)?;
+        self.1.leave_dir(dir, depth, is_last)
+    }
+
+    #[inline]
+    fn elided(&mut self, entries: &[&FilteredEntry], depth: usize) -> Result<()> {
+        self.0.elided(entries, depth)?;
+        self.1.elided(entries, depth)
+    }
+
+    #[inline]
+    fn truncated(
+        &mut self,
+        dir: &FilteredEntry,
+        dirs: usize,
+        files: usize,
+        depth: usize,
+    ) -> Result<()> {
+        self.0.truncated(dir, dirs, files, depth)?;
+        self.1.truncated(dir, dirs, files, depth)
+    }
+
+    #[inline]
+    fn error(&mut self, path: &Path, error: &Error, depth: usize) -> Result<()> {
+        self.0.error(path, error, depth)?;
+        self.1.error(path, error, depth)
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::BufReader,
     path::{Path, PathBuf},
@@ -16,6 +17,7 @@ struct TestDescription {
     args: Vec<String>,
     current_directory: Option<String>,
     assert_file_contents: Option<Vec<String>>,
+    env: Option<HashMap<String, String>>,
 }
 
 #[fixtures(
@@ -41,15 +43,20 @@ fn conformance(test_dir: &Path) {
         None => test_working_dir.as_ref().to_path_buf(),
     };
 
+    let env = test_description.env.unwrap_or_default();
+
     let reference_binary = std::env::var_os("TREE_REFERENCE_BIN").unwrap_or("tree".into());
     let reference_output = Command::new(reference_binary)
         .current_dir(&command_current_directory)
+        .envs(&env)
         .args(&test_description.args)
         .output()
         .unwrap();
     let reference_code = reference_output.status.code().unwrap();
-    let reference_stdout = String::from_utf8(reference_output.stdout).unwrap();
-    let reference_stderr = String::from_utf8(reference_output.stderr).unwrap();
+    // Output is compared as bytes, since file names may not be valid UTF-8 (e.g.
+    // with -N)
+    let reference_stdout = reference_output.stdout;
+    let reference_stderr = reference_output.stderr;
 
     struct FileContentAssertion {
         file: String,
@@ -57,62 +64,51 @@ fn conformance(test_dir: &Path) {
         sut_snapshot_path: PathBuf,
     }
 
-    let file_content_assertions = if let Some(files) = test_description.assert_file_contents {
-        Some(
-            files
-                .into_iter()
-                .map(|file| {
-                    let path = test_working_dir.as_ref().join(&file);
-                    let reference_content = match std::fs::read_to_string(&path) {
-                        Ok(content) => format!("{file}:\n\n{content}"),
-                        Err(err) => format!("{file}:\n\n{err}"),
-                    };
-                    if path.exists() {
-                        std::fs::remove_file(&path).unwrap();
-                    }
-                    let sanitised_file_name = file.replace("/", "__");
-                    let reference_snapshot_path =
-                        test_dir.join(format!("file.{sanitised_file_name}.reference.snap"));
-                    std::fs::write(reference_snapshot_path, &reference_content).unwrap();
-                    FileContentAssertion {
-                        file,
-                        reference_content,
-                        sut_snapshot_path: test_dir
-                            .join(format!("file.{sanitised_file_name}.sut.snap")),
-                    }
-                })
-                .collect::<Vec<_>>(),
-        )
-    } else {
-        None
-    };
+    let file_content_assertions = test_description.assert_file_contents.map(|files| {
+        files
+            .into_iter()
+            .map(|file| {
+                let path = test_working_dir.as_ref().join(&file);
+                let reference_content = match std::fs::read_to_string(&path) {
+                    Ok(content) => format!("{file}:\n\n{content}"),
+                    Err(err) => format!("{file}:\n\n{err}"),
+                };
+                if path.exists() {
+                    std::fs::remove_file(&path).unwrap();
+                }
+                let sanitised_file_name = file.replace("/", "__");
+                let reference_snapshot_path =
+                    test_dir.join(format!("file.{sanitised_file_name}.reference.snap"));
+                std::fs::write(reference_snapshot_path, &reference_content).unwrap();
+                FileContentAssertion {
+                    file,
+                    reference_content,
+                    sut_snapshot_path: test_dir
+                        .join(format!("file.{sanitised_file_name}.sut.snap")),
+                }
+            })
+            .collect::<Vec<_>>()
+    });
 
     let code_reference_snapshot_path = test_dir.join("code.reference.snap");
     let stdout_reference_snapshot_path = test_dir.join("stdout.reference.snap");
     let stderr_reference_snapshot_path = test_dir.join("stderr.reference.snap");
 
     std::fs::write(code_reference_snapshot_path, format!("{reference_code}")).unwrap();
-    std::fs::write(
-        stdout_reference_snapshot_path,
-        format!("{reference_stdout}"),
-    )
-    .unwrap();
-    std::fs::write(
-        stderr_reference_snapshot_path,
-        format!("{reference_stderr}"),
-    )
-    .unwrap();
+    std::fs::write(stdout_reference_snapshot_path, &reference_stdout).unwrap();
+    std::fs::write(stderr_reference_snapshot_path, &reference_stderr).unwrap();
 
     let sut_output = Command::cargo_bin("rt")
         .unwrap()
         .current_dir(&command_current_directory)
+        .envs(&env)
         .arg("--compat")
         .args(&test_description.args)
         .output()
         .unwrap();
     let sut_code = sut_output.status.code().unwrap();
-    let sut_stdout = String::from_utf8(sut_output.stdout).unwrap();
-    let sut_stderr = String::from_utf8(sut_output.stderr).unwrap();
+    let sut_stdout = sut_output.stdout;
+    let sut_stderr = sut_output.stderr;
 
     let code_sut_snapshot_path = test_dir.join("code.sut.snap");
     let stdout_sut_snapshot_path = test_dir.join("stdout.sut.snap");
@@ -123,8 +119,16 @@ fn conformance(test_dir: &Path) {
     std::fs::write(stderr_sut_snapshot_path, &sut_stderr).unwrap();
 
     pretty_assertions::assert_eq!(reference_code, sut_code);
-    pretty_assertions::assert_eq!(reference_stdout, sut_stdout);
-    pretty_assertions::assert_eq!(reference_stderr, sut_stderr);
+    pretty_assertions::assert_eq!(
+        String::from_utf8_lossy(&reference_stdout),
+        String::from_utf8_lossy(&sut_stdout)
+    );
+    pretty_assertions::assert_eq!(
+        String::from_utf8_lossy(&reference_stderr),
+        String::from_utf8_lossy(&sut_stderr)
+    );
+    assert_eq!(reference_stdout, sut_stdout);
+    assert_eq!(reference_stderr, sut_stderr);
 
     if let Some(file_content_assertions) = file_content_assertions {
         for FileContentAssertion {
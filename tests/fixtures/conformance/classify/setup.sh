@@ -0,0 +1,15 @@
+#!/bin/bash
+set -e
+
+mkdir a
+touch a/b.txt
+mkdir c
+touch d.txt
+touch e.sh
+chmod +x e.sh
+mkfifo f.fifo
+python3 -c "import socket; socket.socket(socket.AF_UNIX).bind('g.sock')"
+ln -s a h.link
+ln -s e.sh i.link
+ln -s d.txt j.link
+ln -s missing k.link
@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+mkdir -p a/b
+head -c 1500 /dev/zero > a/f1.bin
+head -c 250000 /dev/zero > a/b/big.bin
+head -c 3 /dev/zero > c.txt
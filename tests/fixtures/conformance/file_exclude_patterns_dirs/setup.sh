@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+mkdir a1
+touch a1/c
+touch a2
+mkdir b1
@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+touch a.txt
+touch b.md
+mkdir sub
+touch sub/c.txt
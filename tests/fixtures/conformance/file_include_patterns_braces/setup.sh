@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+touch a.txt
+touch b.txt
+touch '{a,b}.txt'
+touch c.txt
@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+touch a.txt
+touch .txt
+mkdir sub
+touch sub/b.txt
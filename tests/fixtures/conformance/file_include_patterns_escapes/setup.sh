@@ -0,0 +1,8 @@
+#!/bin/bash
+set -e
+
+touch "x[1]"
+touch x1
+touch a1
+touch d1
+touch bb
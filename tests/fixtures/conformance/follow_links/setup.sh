@@ -0,0 +1,12 @@
+#!/bin/bash
+set -e
+
+mkdir a
+touch a/b.txt
+mkdir a/c
+ln -s .. a/c/up.link
+mkdir d
+touch d/e.txt
+ln -s d f.link
+ln -s d/e.txt g.link
+ln -s missing h.link
@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p conf sub other/sub
+printf "*.bak\n/sub/anchored\n" > conf/extra.ignore
+touch a.bak b.txt sub/anchored sub/c.bak other/sub/anchored
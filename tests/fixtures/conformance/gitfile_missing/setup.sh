@@ -0,0 +1,4 @@
+#!/bin/bash
+set -e
+
+touch a
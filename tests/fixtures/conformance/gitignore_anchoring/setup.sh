@@ -0,0 +1,10 @@
+#!/bin/bash
+set -e
+
+printf "parent.txt\n" > .gitignore
+mkdir -p root/build root/sub/build root/sub/deeper
+cd root
+printf "# comment\n\n*.log\nbuild/\n/top_only\nsub/anchored\n" > .gitignore
+touch a.log b.txt top_only sub/top_only sub/anchored sub/deeper/anchored parent.txt build/out sub/build/out
+printf "*.txt\n" > sub/.gitignore
+touch sub/c.txt
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+printf "\\\\#hash\nfoo\\\\ \na\\\\[1\\\\]\n" > .gitignore
+touch "#hash" foo "foo " a1 "a[1]" other
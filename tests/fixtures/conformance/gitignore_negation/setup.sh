@@ -0,0 +1,8 @@
+#!/bin/bash
+set -e
+
+printf "!keep.log\n*.log\n*.tmp\n" > .gitignore
+touch a.log keep.log a.tmp
+mkdir sub
+printf "!*.tmp\n" > sub/.gitignore
+touch sub/b.tmp sub/b.log
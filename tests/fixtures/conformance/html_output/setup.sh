@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p "a/b c"
+touch "a/b c/d.txt" a/e.txt "f&g<h>.txt"
+ln -s a i.link
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p a/b/c d
+touch a/b/c/x.txt a/y.txt "d/q&r.txt"
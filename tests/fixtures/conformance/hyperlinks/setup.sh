@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+mkdir "a b"
+touch "a b/c#d.txt"
+touch "e%f.txt"
+ln -s "a b" g.link
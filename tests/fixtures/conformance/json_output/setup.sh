@@ -0,0 +1,11 @@
+#!/bin/bash
+set -e
+
+touch a.txt
+mkdir b
+touch b/a.txt
+touch 'b/quote"d.txt'
+mkdir c
+mkdir c/d
+touch c/d/e.txt
+ln -s a.txt e.link
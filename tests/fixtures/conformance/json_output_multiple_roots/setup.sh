@@ -0,0 +1,9 @@
+#!/bin/bash
+set -e
+
+mkdir b
+touch b/a.txt
+mkdir b/c
+touch b/c/d.txt
+mkdir c
+touch c/e.txt
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir a
+touch a/b.txt
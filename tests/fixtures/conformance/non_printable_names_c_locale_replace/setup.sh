@@ -0,0 +1,12 @@
+#!/bin/bash
+set -e
+
+touch $'new\nline.txt'
+touch $'tab\tbed.txt'
+touch $'invalid\xffutf8.txt'
+touch 'h'$'\xc3\xa9''llo.txt'
+touch 'quote"d.txt'
+touch 'sp ace.txt'
+mkdir $'dir\x01'
+touch $'dir\x01/a.txt'
+ln -s $'tab\tbed.txt' $'link\x7f'
@@ -0,0 +1,14 @@
+#!/bin/bash
+set -e
+
+touch a.txt
+touch b.txt
+mkdir c
+touch c/a.txt
+mkdir d
+touch d/a.txt
+touch d/b.txt
+mkdir d/c
+mkdir e
+mkdir f
+touch f/a.txt
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p empty docs src/a/b src/c
+touch docs/readme.md src/a/b/notes.txt src/c/x.md top.txt
@@ -0,0 +1,10 @@
+#!/bin/bash
+set -e
+
+mkdir -p a/b c
+head -c 1500 /dev/zero > a/f1.bin
+head -c 250000 /dev/zero > a/b/big.bin
+head -c 9000 /dev/zero > c/mid.bin
+head -c 3 /dev/zero > d.txt
+head -c 3 /dev/zero > e.txt
+head -c 20000 /dev/zero > f.bin
@@ -0,0 +1,15 @@
+#!/bin/bash
+set -e
+
+mkdir dir
+touch dir/a_inner dir/b_inner
+touch a_file b_file
+mkfifo a_fifo b_fifo
+python3 -c "import socket
+for name in (\"a_socket\", \"b_socket\"):
+    socket.socket(socket.AF_UNIX).bind(name)"
+ln -s b_file a_link
+ln -s a_file b_link
+ln -s missing a_dangling
+ln -s dir a_dirlink
+ln -s dir b_dirlink
@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+mkdir dir
+touch dir/a_inner dir/b_inner
+touch a_file b_file
+ln -s dir b_dirlink
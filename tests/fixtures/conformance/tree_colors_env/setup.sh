@@ -0,0 +1,12 @@
+#!/bin/bash
+set -e
+
+touch a.txt
+mkdir b
+touch b/a.txt
+touch b/c.txt
+mkdir c
+touch c/d.sh
+chmod +x c/d.sh
+ln -s a.txt e.link
+ln -s missing f.link
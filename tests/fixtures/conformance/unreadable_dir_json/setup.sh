@@ -0,0 +1,13 @@
+#!/bin/bash
+set -e
+
+touch a.txt
+mkdir b
+touch b/c.txt
+mkdir d
+touch d/e.txt
+chmod 000 d
+mkdir f
+mkdir f/g
+touch f/g/h.txt
+chmod 000 f/g
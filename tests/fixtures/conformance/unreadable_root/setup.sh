@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir a
+touch a/b.txt
+chmod 000 a
@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir dir
+touch dir/a dir/b
+echo "previous output" > out.txt
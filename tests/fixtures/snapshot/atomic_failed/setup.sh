@@ -0,0 +1,4 @@
+#!/bin/bash
+set -e
+
+echo "previous output" > out.json
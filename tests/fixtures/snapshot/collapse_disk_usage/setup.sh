@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p a/b z
+head -c 3000 /dev/zero > a/b/big
+head -c 5000 /dev/zero > z/bigger
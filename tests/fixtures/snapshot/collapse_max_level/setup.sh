@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p src/main/java/com/example/project src/test/java/com/example a/b/c/d e/f empty/dir
+touch src/main/java/com/example/project/App.java src/test/java/com/example/AppTest.java a/b/c/d/x e/f/g pom.xml
@@ -0,0 +1,8 @@
+#!/bin/bash
+set -e
+
+echo "no_icons = true
+full_path = \"relative\"
+" > .riptree.toml
+mkdir b
+touch b/a.txt
@@ -0,0 +1,8 @@
+#!/bin/bash
+set -e
+
+echo "no_icons = true
+no_gitignore = true
+" > .riptree.toml
+echo "*.log" > extra.ignore
+touch a.txt b.log
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -e
+
+echo "no_icons = true
+show_hidden_files = true
+file_exclude_patterns = [\"*.toml\"]
+" > user_config.toml
+echo "max_level = 1
+" > .riptree.toml
+touch a.txt
+touch .b.txt
+mkdir c
+touch c/a.txt
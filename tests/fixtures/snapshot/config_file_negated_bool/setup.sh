@@ -0,0 +1,9 @@
+#!/bin/bash
+set -e
+
+echo "no_icons = true
+show_hidden_files = true
+" > user_config.toml
+echo "show_hidden_files = false
+" > .riptree.toml
+touch a.txt .b.txt
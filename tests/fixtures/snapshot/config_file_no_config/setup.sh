@@ -0,0 +1,12 @@
+#!/bin/bash
+set -e
+
+echo "no_icons = true
+max_level = 1
+" > .riptree.toml
+touch a.txt
+mkdir b
+touch b/a.txt
+mkdir c
+mkdir c/d
+touch c/d/a.txt
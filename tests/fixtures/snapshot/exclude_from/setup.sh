@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+mkdir -p src/gen docs
+printf "# Generated code\nsrc/gen/\n\n*.lock\r\n lead\n" > excludes.txt
+printf "*.md\n" > more.txt
+touch Cargo.lock " lead" lead src/main.rs src/gen/api.rs docs/index.md docs/notes.txt
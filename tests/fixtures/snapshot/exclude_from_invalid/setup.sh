@@ -0,0 +1,4 @@
+#!/bin/bash
+set -e
+
+printf "# Valid\n*.lock\n\nsrc/[a-\n" > excludes.txt
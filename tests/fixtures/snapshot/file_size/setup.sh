@@ -0,0 +1,8 @@
+#!/bin/bash
+set -e
+
+mkdir sub
+head -c 2000 /dev/zero > sub/big
+head -c 1024 /dev/zero > exact
+head -c 10 /dev/zero > small
+touch empty
@@ -0,0 +1,8 @@
+#!/bin/bash
+set -e
+
+mkdir sub
+touch -d "2020-01-01 00:00:00" old sub/old
+touch -d "2023-06-01 00:00:00" middle
+touch -d "2 days ago" recent
+touch sub/new
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+touch -d "2 days ago" old
+touch new
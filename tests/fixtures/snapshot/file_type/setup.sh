@@ -0,0 +1,12 @@
+#!/bin/bash
+set -e
+
+mkdir -p dir/empty sub
+touch empty_file
+echo data > file
+echo "#!/bin/sh" > script
+chmod +x script
+ln -s file link
+mkfifo pipe
+python3 -c "import socket; socket.socket(socket.AF_UNIX).bind(\"socket\")"
+touch sub/a
@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+mkdir -p a/b c
+touch a/b/file c/file
+ln -s ../c/file a/link
+touch top
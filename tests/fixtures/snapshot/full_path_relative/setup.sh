@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p a/b c
+touch a/b/d.txt a/e.txt c/f.txt
+ln -s ../a c/g.link
@@ -0,0 +1,7 @@
+#!/bin/bash
+set -e
+
+mkdir -p proj/build proj/sub/build proj/other/build
+printf "/build\n" > extra.ignore
+printf "/other/build\n" > proj/.gitignore
+touch proj/build/x proj/sub/build/y proj/other/build/z
@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+printf '[core]\n\texcludesFile = ~/global_ignore\n' > .gitconfig
+echo '[a-' > global_ignore
+touch a
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p a b/00Tree.html
+touch a/c
@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p src docs
+printf "# Sources\nsrc/*.rs\n\nREADME*\n" > includes.txt
+touch README.md src/main.rs src/lib.c src/lib.h docs/index.md
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p a
+touch 1.txt 2.txt 3.txt a/x.txt a/y.txt a/z.txt
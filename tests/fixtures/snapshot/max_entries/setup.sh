@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p d1/sub
+touch d1/a d1/b d1/sub/x
+touch f1 f2 f3 f4 f5
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p logs/keep
+touch a.log important.log logs/b.log logs/keep/c.log logs/keep/d.log "!literal"
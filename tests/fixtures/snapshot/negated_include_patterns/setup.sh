@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p src/gen
+touch src/main.rs src/main_test.rs src/lib.rs src/gen/api.rs README.md
@@ -0,0 +1,10 @@
+#!/bin/bash
+set -e
+
+mkdir -p crates/a/target crates/b/src target/crates/c/target
+touch crates/a/target/out crates/b/src/lib.rs target/crates/c/target/out
+mkdir -p docs/build build
+touch docs/build/index.html build/index.html
+touch debug debug.log
+mkdir logs
+touch logs/debug
@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p src/a/b tests other/src
+touch src/main.rs src/a/lib.rs src/a/b/mod.rs src/a/b/notes.md tests/it.rs other/src/x.rs build.rs
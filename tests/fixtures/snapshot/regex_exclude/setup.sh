@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p src/gen
+touch src/main.rs src/schema_pb2.py src/Schema_PB2.pyi src/gen/api.rs src/gen/keep.txt api.rs
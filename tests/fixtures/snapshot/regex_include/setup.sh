@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+mkdir -p src/gen docs
+touch src/main.rs src/lib.gen.rs src/gen/api.rs src/gen/notes.md docs/a.md README.md
@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p src/main/java/com/example/util src/test/java docs/api empty build/classes
+touch src/main/java/com/example/App.java src/main/java/com/example/util/a.java src/main/java/com/example/util/.hidden src/test/java/AppTest.java docs/api/index.md build/classes/App.class README.md
+printf "build/\n" > .gitignore
@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+mkdir -p a/b
+touch a/b/c a/d e
+ln -s .. a/b/loop
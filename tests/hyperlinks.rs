@@ -0,0 +1,53 @@
+use std::process::Command;
+
+use assert_cmd::cargo::cargo_bin;
+use riptree2::hyperlink::{HyperlinkMode, Hyperlinks};
+use tempdir::TempDir;
+
+static OSC_8: &str = "\x1b]8;;";
+
+#[test]
+fn from_mode() {
+    assert!(Hyperlinks::from_mode(HyperlinkMode::Never, true).is_none());
+    assert!(Hyperlinks::from_mode(HyperlinkMode::Auto, false).is_none());
+    assert!(Hyperlinks::from_mode(HyperlinkMode::Auto, true).is_some());
+    assert!(Hyperlinks::from_mode(HyperlinkMode::Always, false).is_some());
+}
+
+/// Runs rt with a terminal as stdout, using script(1), and returns what was
+/// written to the terminal, or `None` if script is not available.
+fn run_in_terminal(dir: &TempDir, args: &str) -> Option<String> {
+    let command = format!("{} {args}", cargo_bin("rt").to_string_lossy());
+    let output = Command::new("script")
+        .args(["-qec", &command, "/dev/null"])
+        .current_dir(dir.path())
+        .env("RT_CONFIG", "")
+        .output()
+        .ok()?;
+    assert!(output.status.success());
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+fn auto_in_terminal() {
+    let dir = TempDir::new("hyperlinks").unwrap();
+    std::fs::write(dir.path().join("a"), "").unwrap();
+
+    let Some(stdout) = run_in_terminal(&dir, "--no-icons --hyperlink=auto") else {
+        return;
+    };
+    assert!(stdout.contains(OSC_8));
+}
+
+#[test]
+fn auto_with_output_file() {
+    let dir = TempDir::new("hyperlinks").unwrap();
+    std::fs::write(dir.path().join("a"), "").unwrap();
+
+    if run_in_terminal(&dir, "--no-icons --hyperlink=auto -o out.txt").is_none() {
+        return;
+    }
+    let output = std::fs::read_to_string(dir.path().join("out.txt")).unwrap();
+    assert!(output.contains("── a\n"));
+    assert!(!output.contains(OSC_8));
+}
@@ -0,0 +1,70 @@
+use riptree2::{error::Error, full_path::FullPath, options::TreeOptions};
+
+#[test]
+fn valid_options() {
+    let options = TreeOptions::builder()
+        .max_level(2)
+        .max_entries(10)
+        .file_include_patterns(["*.rs", "src/*"])
+        .file_exclude_regexes(["^target$"])
+        .build();
+
+    assert!(options.is_ok());
+}
+
+#[test]
+fn invalid_pattern() {
+    let result = TreeOptions::builder()
+        .file_include_patterns(["*.rs", "[a-"])
+        .build();
+
+    assert!(matches!(result, Err(Error::InvalidPattern { pattern, .. }) if pattern == "[a-"));
+}
+
+#[test]
+fn invalid_regex() {
+    let result = TreeOptions::builder()
+        .file_exclude_regexes(["(unclosed"])
+        .build();
+
+    assert!(matches!(result, Err(Error::InvalidRegex { pattern, .. }) if pattern == "(unclosed"));
+}
+
+#[test]
+fn zero_max_level() {
+    let result = TreeOptions::builder().max_level(0).build();
+
+    assert!(matches!(result, Err(Error::InvalidOptions(_))));
+}
+
+#[test]
+fn zero_max_entries() {
+    let result = TreeOptions::builder().max_entries(0).build();
+
+    assert!(matches!(result, Err(Error::InvalidOptions(_))));
+}
+
+#[test]
+fn compat_conflicts() {
+    let builders = [
+        TreeOptions::builder().full_path(FullPath::Absolute),
+        TreeOptions::builder().max_entries(5),
+        TreeOptions::builder().file_include_regexes(["^src$"]),
+        TreeOptions::builder().show_truncated(true),
+        TreeOptions::builder().collapse(true),
+    ];
+
+    for builder in builders {
+        let result = builder.compat(true).build();
+        assert!(matches!(result, Err(Error::InvalidOptions(_))));
+    }
+}
+
+#[test]
+fn match_dirs_requires_compat() {
+    let result = TreeOptions::builder().match_dirs(true).build();
+    assert!(matches!(result, Err(Error::InvalidOptions(_))));
+
+    let result = TreeOptions::builder().match_dirs(true).compat(true).build();
+    assert!(result.is_ok());
+}
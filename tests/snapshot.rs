@@ -1,4 +1,4 @@
-use std::{fs::File, io::BufReader, path::Path, process::Command};
+use std::{collections::HashMap, fs::File, io::BufReader, path::Path, process::Command};
 
 use assert_cmd::cargo::CommandCargoExt;
 use fixtures::fixtures;
@@ -9,6 +9,13 @@ use test_utils::{TestWorkingDir, snapshot::assert_snapshot};
 struct TestDescription {
     description: String,
     args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+    /// Files in the working directory whose contents are snapshotted after the
+    /// run, e.g. to check an -o file.
+    assert_file_contents: Option<Vec<String>>,
+    /// Whether the names of the files left in the working directory are
+    /// snapshotted after the run, e.g. to check that no temporary file is left.
+    assert_file_names: Option<bool>,
 }
 #[fixtures(
     ["tests/fixtures/snapshot/*"],
@@ -30,6 +37,9 @@ fn snapshot(test_dir: &Path) {
     let sut_output = Command::cargo_bin("rt")
         .unwrap()
         .current_dir(&test_working_dir)
+        // Prevent the user config file from affecting snapshots
+        .env("RT_CONFIG", "")
+        .envs(test_description.env.unwrap_or_default())
         .args(&test_description.args)
         .output()
         .unwrap();
@@ -44,6 +54,27 @@ fn snapshot(test_dir: &Path) {
     assert_snapshot(code_snapshot_path, sut_code);
     assert_snapshot(stdout_snapshot_path, sut_stdout);
     assert_snapshot(stderr_snapshot_path, sut_stderr);
+
+    for file in test_description.assert_file_contents.unwrap_or_default() {
+        let content = match std::fs::read_to_string(test_working_dir.as_ref().join(&file)) {
+            Ok(content) => content,
+            Err(err) => err.to_string(),
+        };
+        let sanitised_file_name = file.replace("/", "__");
+        assert_snapshot(
+            test_dir.join(format!("file.{sanitised_file_name}.snap")),
+            content,
+        );
+    }
+
+    if test_description.assert_file_names.unwrap_or_default() {
+        let mut file_names = std::fs::read_dir(&test_working_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        file_names.sort();
+        assert_snapshot(test_dir.join("files.snap"), file_names.join("\n"));
+    }
 }
 
 // #[fixtures(["tests/fixtures/snapshot/*.skip"])]
@@ -0,0 +1,139 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use pretty_assertions::assert_eq;
+use riptree2::{
+    error::{Error, Result},
+    filter::FilteredEntry,
+    options::TreeOptions,
+    tree::Tree,
+    visitor::TreeVisitor,
+};
+use test_utils::TestWorkingDir;
+
+/// Records each callback as a line, e.g. "entry a 1 false", with the path
+/// relative to the root.
+#[derive(Default)]
+struct RecordingVisitor {
+    root: PathBuf,
+    events: Vec<String>,
+}
+
+impl RecordingVisitor {
+    fn name(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap()
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+impl TreeVisitor for RecordingVisitor {
+    fn entry(
+        &mut self,
+        entry: &FilteredEntry,
+        _link_target: Option<&Path>,
+        depth: usize,
+        is_last: bool,
+    ) -> Result<()> {
+        let name = self.name(entry.as_ref().path());
+        self.events.push(format!("entry {name} {depth} {is_last}"));
+        Ok(())
+    }
+
+    fn enter_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        let name = self.name(dir.as_ref().path());
+        self.events
+            .push(format!("enter_dir {name} {depth} {is_last}"));
+        Ok(())
+    }
+
+    fn leave_dir(&mut self, dir: &FilteredEntry, depth: usize, is_last: bool) -> Result<()> {
+        let name = self.name(dir.as_ref().path());
+        self.events
+            .push(format!("leave_dir {name} {depth} {is_last}"));
+        Ok(())
+    }
+
+    fn error(&mut self, path: &Path, error: &Error, depth: usize) -> Result<()> {
+        let name = self.name(path);
+        assert!(matches!(error, Error::RecursiveLink { .. }));
+        self.events.push(format!("error {name} {depth}"));
+        Ok(())
+    }
+}
+
+fn visit(options: TreeOptions) -> Vec<String> {
+    let test_working_dir = TestWorkingDir::new(Path::new("tests/fixtures/visitor"));
+    let root = test_working_dir.as_ref().to_path_buf();
+    let tree = Tree::new(root.clone(), Arc::new(options)).unwrap();
+    let mut visitor = RecordingVisitor {
+        root: root.clone(),
+        ..Default::default()
+    };
+    tree.visit(&mut visitor).unwrap();
+
+    // The in-memory tree is replayed in the same order
+    let mut replay = RecordingVisitor {
+        root,
+        ..Default::default()
+    };
+    tree.build().unwrap().visit(&mut replay).unwrap();
+    assert_eq!(replay.events, visitor.events);
+
+    visitor.events
+}
+
+#[test]
+fn visits_entries_in_order() {
+    let events = visit(TreeOptions::default());
+
+    assert_eq!(
+        events,
+        [
+            "entry a 1 false",
+            "enter_dir a 1 false",
+            "entry a/b 2 false",
+            "enter_dir a/b 2 false",
+            "entry a/b/c 3 false",
+            "entry a/b/loop 3 true",
+            "leave_dir a/b 2 false",
+            "entry a/d 2 true",
+            "leave_dir a 1 false",
+            "entry e 1 true",
+        ]
+    );
+}
+
+#[test]
+fn reports_errors_before_entries() {
+    let options = TreeOptions::builder().follow_links(true).build().unwrap();
+    let events = visit(options);
+
+    assert_eq!(
+        events,
+        [
+            "entry a 1 false",
+            "enter_dir a 1 false",
+            "entry a/b 2 false",
+            "enter_dir a/b 2 false",
+            "entry a/b/c 3 false",
+            "entry a/b/loop 3 true",
+            "enter_dir a/b/loop 3 true",
+            "entry a/b/loop/b 4 false",
+            "enter_dir a/b/loop/b 4 false",
+            "entry a/b/loop/b/c 5 false",
+            "error a/b/loop/b/loop 5",
+            "entry a/b/loop/b/loop 5 true",
+            "leave_dir a/b/loop/b 4 false",
+            "entry a/b/loop/d 4 true",
+            "leave_dir a/b/loop 3 true",
+            "leave_dir a/b 2 false",
+            "entry a/d 2 true",
+            "leave_dir a 1 false",
+            "entry e 1 true",
+        ]
+    );
+}